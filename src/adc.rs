@@ -8,7 +8,8 @@
     Temperature in °C = (110-30)/(VtempCal110::get().read()-VtempCal30::get().read()) * (adc_sample - VtempCal30::get().read()) + 30
 */
 
-use crate::{gpio::*, signature::VrefCal, signature::VDDA_CALIB, stm32};
+use crate::{dma::PeriAddress, gpio::*, signature::VrefCal, signature::VDDA_CALIB, stm32};
+use crate::rcc::{Enable, Reset};
 use core::fmt;
 use embedded_hal::adc::{Channel, OneShot};
 
@@ -614,22 +615,18 @@ impl<ADC> fmt::Debug for Adc<ADC> {
 }
 
 macro_rules! adc {
-    ($($adc_type:ident => ($constructor_fn_name:ident, $common_type:ident, $rcc_enr_reg:ident, $rcc_enr_field: ident, $rcc_rst_reg: ident, $rcc_rst_field: ident)),+ $(,)*) => {
+    ($($adc_type:ident => ($constructor_fn_name:ident, $common_type:ident)),+ $(,)*) => {
         $(
             impl Adc<stm32::$adc_type> {
                 /// Enables the ADC clock, resets the peripheral (optionally), runs calibration and applies the supplied config
                 /// # Arguments
                 /// * `reset` - should a reset be performed. This is provided because on some devices multiple ADCs share the same common reset
                 pub fn $constructor_fn_name(adc: stm32::$adc_type, reset: bool, config: config::AdcConfig) -> Adc<stm32::$adc_type> {
-                    unsafe {
-                        let rcc = &(*stm32::RCC::ptr());
-                        //Enable the common clock
-                        rcc.$rcc_enr_reg.modify(|_, w| w.$rcc_enr_field().set_bit());
-                        if reset {
-                            //Reset the peripheral(s)
-                            rcc.$rcc_rst_reg.modify(|_, w| w.$rcc_rst_field().set_bit());
-                            rcc.$rcc_rst_reg.modify(|_, w| w.$rcc_rst_field().clear_bit());
-                        }
+                    //Enable the common clock
+                    stm32::$adc_type::enable();
+                    if reset {
+                        //Reset the peripheral(s)
+                        stm32::$adc_type::reset();
                     }
 
                     let mut s = Self {
@@ -978,6 +975,14 @@ macro_rules! adc {
                 }
             }
 
+            unsafe impl PeriAddress for Adc<stm32::$adc_type> {
+                type MemSize = u16;
+
+                fn address(&self) -> u32 {
+                    &self.adc_reg.dr as *const _ as u32
+                }
+            }
+
             impl<PIN> OneShot<stm32::$adc_type, u16, PIN> for Adc<stm32::$adc_type>
             where
                 PIN: Channel<stm32::$adc_type, ID=u8>,
@@ -1022,7 +1027,7 @@ macro_rules! adc {
     feature = "stm32f469",
     feature = "stm32f479",
 ))]
-adc!(ADC1 => (adc1, ADC_COMMON, apb2enr, adc1en, apb2rstr, adcrst));
+adc!(ADC1 => (adc1, ADC_COMMON));
 
 #[cfg(any(
     feature = "stm32f405",
@@ -1037,7 +1042,7 @@ adc!(ADC1 => (adc1, ADC_COMMON, apb2enr, adc1en, apb2rstr, adcrst));
     feature = "stm32f469",
     feature = "stm32f479",
 ))]
-adc!(ADC2 => (adc2, ADC_COMMON, apb2enr, adc2en, apb2rstr, adcrst));
+adc!(ADC2 => (adc2, ADC_COMMON));
 
 #[cfg(any(
     feature = "stm32f405",
@@ -1052,7 +1057,7 @@ adc!(ADC2 => (adc2, ADC_COMMON, apb2enr, adc2en, apb2rstr, adcrst));
     feature = "stm32f469",
     feature = "stm32f479",
 ))]
-adc!(ADC3 => (adc3, ADC_COMMON, apb2enr, adc3en, apb2rstr, adcrst));
+adc!(ADC3 => (adc3, ADC_COMMON));
 
 #[cfg(feature = "stm32f401")]
 adc_pins!(
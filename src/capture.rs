@@ -0,0 +1,578 @@
+//! Input capture and PWM input
+//!
+//! [`Capture`] timestamps edges on every channel of a timer that has a pin, [`PwmInput`] uses
+//! the first two channels of a timer to measure the period and pulse width of the signal on its
+//! channel 1 pin.
+//!
+//! ```
+//! let gpioa = dp.GPIOA.split();
+//! let pwm_input = PwmInput::tim2(dp.TIM2, gpioa.pa0.into_alternate_af1(), 1.mhz(), clocks);
+//!
+//! if let (Some(freq), Some(high)) = (pwm_input.frequency(), pwm_input.pulse_width()) {
+//!     // ...
+//! }
+//! ```
+//!
+//! The capture registers of TIM2 and TIM5 are 32 bits wide, on all other timers the longest
+//! period that can be measured is 65535 counter ticks. Longer periods are reported as overflows
+//! instead of wrapped values.
+
+use cast::u64;
+
+use crate::pwm::{Channel, PinC1, Pins};
+use crate::rcc::{BusTimerClock, Clocks, Enable, Reset};
+use crate::time::{Duration, Hertz};
+
+use crate::stm32::{TIM1, TIM11, TIM5, TIM9};
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::stm32::{TIM10, TIM2, TIM3, TIM4};
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::stm32::{TIM12, TIM13, TIM14, TIM8};
+
+/// Input capture error
+#[derive(Debug)]
+pub enum Error {
+    /// A capture occurred before the previous one was read, the previous value is lost
+    Overcapture,
+    #[doc(hidden)]
+    _Extensible,
+}
+
+/// Edge of the input signal that triggers a capture
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edge {
+    Rising,
+    Falling,
+    Both,
+}
+
+/// Number of edges per capture
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Prescaler {
+    Div1 = 0,
+    Div2 = 1,
+    Div4 = 2,
+    Div8 = 3,
+}
+
+/// Input capture channel configuration
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    edge: Edge,
+    prescaler: Prescaler,
+    filter: u8,
+}
+
+impl Config {
+    pub fn edge(mut self, edge: Edge) -> Self {
+        self.edge = edge;
+        self
+    }
+
+    pub fn prescaler(mut self, prescaler: Prescaler) -> Self {
+        self.prescaler = prescaler;
+        self
+    }
+
+    /// Sets the ICxF digital filter value, from 0 (no filter) to 15
+    ///
+    /// Higher values require more consecutive samples at a lower sampling rate before an edge
+    /// is accepted, see the reference manual for the exact sampling frequency and length.
+    pub fn filter(mut self, filter: u8) -> Self {
+        self.filter = core::cmp::min(filter, 15);
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            edge: Edge::Rising,
+            prescaler: Prescaler::Div1,
+            filter: 0,
+        }
+    }
+}
+
+/// Input capture on all channels of a timer that have a pin
+pub struct Capture<TIM, PINS> {
+    tim: TIM,
+    pins: PINS,
+    resolution: Hertz,
+    clk: Hertz,
+}
+
+/// Period and pulse width measurement of the signal on the channel 1 pin of a timer
+pub struct PwmInput<TIM, PIN> {
+    tim: TIM,
+    pin: PIN,
+    resolution: Hertz,
+    clk: Hertz,
+}
+
+/// Converts a number of ticks of a counter running at `clk` into a duration
+fn ticks_to_duration(ticks: u32, clk: Hertz) -> Duration {
+    Duration(u64(ticks) * 1_000_000 / u64(clk.0))
+}
+
+// Update interrupt flag, only set by counter overflows since URS is set
+const UIF: u32 = 1;
+
+// Sets the prescaler for `resolution`, runs the counter over its full range and returns the
+// actual counter frequency
+macro_rules! start_counter {
+    ($tim:expr, $resolution:expr, $clk:expr, $bits:ident) => {{
+        let clk = $clk.0;
+        let resolution: Hertz = $resolution.into();
+        // the prescaler divides by 1 to 65536
+        let psc = ((clk / resolution.0.max(1)).max(1) - 1).min(0xffff) as u16;
+
+        $tim.psc.write(|w| w.psc().bits(psc));
+        $tim.arr.write(|w| unsafe { w.bits(u32::from($bits::max_value())) });
+        // load the prescaler
+        $tim.egr.write(|w| w.ug().set_bit());
+        $tim.cr1.modify(|_, w| w.cen().set_bit());
+
+        Hertz(clk / (u32::from(psc) + 1))
+    }};
+}
+
+macro_rules! hal {
+    ($($TIM:ident: ($tim:ident, $bits:ident, [
+        $($C:ident: ($index:expr, $ccmr:ident, $ccr:ident),)+
+    ]),)+) => {
+        $(
+            impl<PINS> Capture<$TIM, PINS> {
+                /// Configures a timer for input capture on the channels that have a pin
+                ///
+                /// The counter runs at `resolution`, or the closest frequency the prescaler allows.
+                /// All channels capture on the rising edge until reconfigured with `configure`.
+                pub fn $tim<CHANNELS, T>(tim: $TIM, pins: PINS, resolution: T, clocks: Clocks) -> Self
+                where
+                    PINS: Pins<$TIM, CHANNELS>,
+                    T: Into<Hertz>,
+                {
+                    // enable and reset peripheral to a clean slate state
+                    $TIM::enable();
+                    $TIM::reset();
+
+                    let mut capture = Capture {
+                        tim,
+                        pins,
+                        resolution: resolution.into(),
+                        clk: Hertz(0),
+                    };
+
+                    $(
+                        if PINS::$C {
+                            capture.configure(Channel::$C, &Config::default());
+                        }
+                    )+
+
+                    capture.reconfigure(clocks);
+                    capture
+                }
+
+                /// Updates the counter frequency after the clocks were reconfigured with
+                /// `CFGR::freeze`
+                ///
+                /// The counter restarts, values captured before have to be discarded.
+                pub fn reconfigure(&mut self, clocks: Clocks) {
+                    let clk = $TIM::timer_clock(&clocks);
+                    self.clk = start_counter!(self.tim, self.resolution, clk, $bits);
+                }
+
+                /// Configures the edge, prescaler and filter of a channel and enables it
+                pub fn configure(&mut self, channel: Channel, config: &Config) {
+                    let i = channel as u32;
+
+                    // CCxS can only be written while the channel is disabled
+                    self.tim.ccer.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << (4 * i))) });
+
+                    // CCxS = 01: input capture mapped on the channel's own input
+                    let bits = 0b01 | (config.prescaler as u32) << 2 | u32::from(config.filter) << 4;
+                    match channel {
+                        $(
+                            Channel::$C => self.tim.$ccmr().modify(|r, w| unsafe {
+                                w.bits(r.bits() & !(0xff << (8 * ($index % 2))) | bits << (8 * ($index % 2)))
+                            }),
+                        )+
+                        #[allow(unreachable_patterns)]
+                        _ => return,
+                    }
+
+                    let polarity = match config.edge {
+                        Edge::Rising => 0b0000,
+                        Edge::Falling => 0b0010,
+                        Edge::Both => 0b1010,
+                    };
+                    self.tim.ccer.modify(|r, w| unsafe {
+                        w.bits(r.bits() & !(0b1111 << (4 * i)) | (polarity | 1) << (4 * i))
+                    });
+                }
+
+                /// Starts listening for captures on `channel`
+                pub fn listen(&mut self, channel: Channel) {
+                    self.tim.dier.modify(|r, w| unsafe { w.bits(r.bits() | 1 << (channel as u32 + 1)) });
+                }
+
+                /// Stops listening for captures on `channel`
+                pub fn unlisten(&mut self, channel: Channel) {
+                    self.tim.dier.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << (channel as u32 + 1))) });
+                }
+
+                /// Returns `true` if a new value has been captured on `channel`
+                pub fn is_captured(&self, channel: Channel) -> bool {
+                    self.tim.sr.read().bits() & 1 << (channel as u32 + 1) != 0
+                }
+
+                /// Returns the counter value of the last capture on `channel`
+                ///
+                /// Reading the value clears the capture flag. If a capture was missed the flags are
+                /// cleared and `Error::Overcapture` is returned instead.
+                pub fn capture(&mut self, channel: Channel) -> nb::Result<$bits, Error> {
+                    let i = channel as u32;
+                    let sr = self.tim.sr.read().bits();
+
+                    if sr & 1 << (i + 9) != 0 {
+                        // The flags are cleared by writing 0, writing 1 leaves them unchanged
+                        self.tim.sr.write(|w| unsafe { w.bits(!(1 << (i + 9) | 1 << (i + 1))) });
+                        Err(nb::Error::Other(Error::Overcapture))
+                    } else if sr & 1 << (i + 1) == 0 {
+                        Err(nb::Error::WouldBlock)
+                    } else {
+                        // reading the capture register clears the capture flag
+                        match channel {
+                            $(Channel::$C => Ok(self.tim.$ccr.read().bits() as $bits),)+
+                            #[allow(unreachable_patterns)]
+                            _ => Err(nb::Error::WouldBlock),
+                        }
+                    }
+                }
+
+                /// Returns the frequency the counter runs at
+                pub fn counter_frequency(&self) -> Hertz {
+                    self.clk
+                }
+
+                /// Converts a number of counter ticks, e.g. the difference of two captures, into
+                /// a duration
+                pub fn ticks_to_duration(&self, ticks: $bits) -> Duration {
+                    ticks_to_duration(ticks.into(), self.clk)
+                }
+
+                /// Stops the counter and releases the timer and the pins
+                pub fn release(self) -> ($TIM, PINS) {
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    (self.tim, self.pins)
+                }
+            }
+        )+
+    }
+}
+
+macro_rules! pwm_input {
+    ($($TIM:ident: ($tim:ident, $bits:ident),)+) => {
+        $(
+            impl<PIN> PwmInput<$TIM, PIN> {
+                /// Configures a timer to measure the signal on its channel 1 pin
+                ///
+                /// The counter runs at `resolution`, or the closest frequency the prescaler allows,
+                /// and is reset on every rising edge. Channel 1 captures the period and channel 2
+                /// the pulse width.
+                pub fn $tim<T>(tim: $TIM, pin: PIN, resolution: T, clocks: Clocks) -> Self
+                where
+                    PIN: PinC1<$TIM>,
+                    T: Into<Hertz>,
+                {
+                    // enable and reset peripheral to a clean slate state
+                    $TIM::enable();
+                    $TIM::reset();
+
+                    // IC1 on TI1 (CC1S = 01), IC2 on TI1 as well (CC2S = 10)
+                    tim.ccmr1_input().write(|w| unsafe { w.bits(0b10 << 8 | 0b01) });
+                    // IC1 captures on the rising, IC2 on the falling edge
+                    tim.ccer.write(|w| unsafe { w.bits(1 << 0 | 1 << 4 | 1 << 5) });
+                    // slave mode reset (SMS = 100) triggered by TI1FP1 (TS = 101)
+                    tim.smcr.write(|w| unsafe { w.bits(0b101 << 4 | 0b100) });
+                    // only counter overflows set the update flag, not the resets
+                    tim.cr1.modify(|_, w| w.urs().set_bit());
+
+                    let mut pwm_input = PwmInput {
+                        tim,
+                        pin,
+                        resolution: resolution.into(),
+                        clk: Hertz(0),
+                    };
+                    pwm_input.reconfigure(clocks);
+                    pwm_input
+                }
+
+                /// Updates the counter frequency after the clocks were reconfigured with
+                /// `CFGR::freeze`
+                ///
+                /// The counter restarts, so the next period is measured from the next rising
+                /// edge on.
+                pub fn reconfigure(&mut self, clocks: Clocks) {
+                    let clk = $TIM::timer_clock(&clocks);
+                    self.clk = start_counter!(self.tim, self.resolution, clk, $bits);
+                }
+
+                /// Returns the length of the last period in counter ticks, 0 until the first
+                /// period has been measured
+                pub fn period_ticks(&self) -> $bits {
+                    self.tim.ccr1.read().bits() as $bits
+                }
+
+                /// Returns the length of the last high pulse in counter ticks
+                pub fn pulse_ticks(&self) -> $bits {
+                    self.tim.ccr2.read().bits() as $bits
+                }
+
+                /// Returns `true` if the counter wrapped around without seeing a rising edge
+                ///
+                /// This happens when the period is longer than the counter can measure or the
+                /// signal stopped. The measurements are invalid until `clear_overflow` is called.
+                pub fn is_overflowed(&self) -> bool {
+                    self.tim.sr.read().bits() & UIF != 0
+                }
+
+                /// Clears the overflow flag to measure again
+                pub fn clear_overflow(&mut self) {
+                    // the flags are cleared by writing 0, writing 1 leaves them unchanged
+                    self.tim.sr.write(|w| unsafe { w.bits(!UIF) });
+                }
+
+                /// Returns the length of the last period in counter ticks, `None` until the
+                /// first period has been measured or after an overflow
+                fn valid_period_ticks(&self) -> Option<$bits> {
+                    match self.period_ticks() {
+                        0 => None,
+                        _ if self.is_overflowed() => None,
+                        ticks => Some(ticks),
+                    }
+                }
+
+                /// Returns the frequency of the signal, `None` until the first period has been
+                /// measured or after an overflow
+                pub fn frequency(&self) -> Option<Hertz> {
+                    self.valid_period_ticks().map(|ticks| Hertz(self.clk.0 / u32::from(ticks)))
+                }
+
+                /// Returns the period of the signal, `None` until the first period has been
+                /// measured or after an overflow
+                pub fn period(&self) -> Option<Duration> {
+                    self.valid_period_ticks().map(|ticks| ticks_to_duration(ticks.into(), self.clk))
+                }
+
+                /// Returns the length of the last high pulse, `None` until the first period has
+                /// been measured or after an overflow
+                pub fn pulse_width(&self) -> Option<Duration> {
+                    self.valid_period_ticks()
+                        .map(|_| ticks_to_duration(self.pulse_ticks().into(), self.clk))
+                }
+
+                /// Returns the last high pulse and period in counter ticks, `None` until the
+                /// first period has been measured or after an overflow
+                ///
+                /// The duty cycle is `pulse / period`.
+                pub fn duty_cycle(&self) -> Option<($bits, $bits)> {
+                    self.valid_period_ticks().map(|period| (self.pulse_ticks(), period))
+                }
+
+                /// Returns the frequency the counter runs at
+                pub fn counter_frequency(&self) -> Hertz {
+                    self.clk
+                }
+
+                /// Stops the counter and releases the timer and the pin
+                pub fn release(self) -> ($TIM, PIN) {
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    (self.tim, self.pin)
+                }
+            }
+        )+
+    }
+}
+
+hal! {
+    TIM1: (tim1, u16, [
+        C1: (0, ccmr1_input, ccr1),
+        C2: (1, ccmr1_input, ccr2),
+        C3: (2, ccmr2_input, ccr3),
+        C4: (3, ccmr2_input, ccr4),
+    ]),
+    TIM5: (tim5, u32, [
+        C1: (0, ccmr1_input, ccr1),
+        C2: (1, ccmr1_input, ccr2),
+        C3: (2, ccmr2_input, ccr3),
+        C4: (3, ccmr2_input, ccr4),
+    ]),
+    TIM9: (tim9, u16, [
+        C1: (0, ccmr1_input, ccr1),
+        C2: (1, ccmr1_input, ccr2),
+    ]),
+    TIM11: (tim11, u16, [
+        C1: (0, ccmr1_input, ccr1),
+    ]),
+}
+
+pwm_input! {
+    TIM1: (tim1, u16),
+    TIM5: (tim5, u32),
+    TIM9: (tim9, u16),
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+hal! {
+    TIM2: (tim2, u32, [
+        C1: (0, ccmr1_input, ccr1),
+        C2: (1, ccmr1_input, ccr2),
+        C3: (2, ccmr2_input, ccr3),
+        C4: (3, ccmr2_input, ccr4),
+    ]),
+    TIM3: (tim3, u16, [
+        C1: (0, ccmr1_input, ccr1),
+        C2: (1, ccmr1_input, ccr2),
+        C3: (2, ccmr2_input, ccr3),
+        C4: (3, ccmr2_input, ccr4),
+    ]),
+    TIM4: (tim4, u16, [
+        C1: (0, ccmr1_input, ccr1),
+        C2: (1, ccmr1_input, ccr2),
+        C3: (2, ccmr2_input, ccr3),
+        C4: (3, ccmr2_input, ccr4),
+    ]),
+    TIM10: (tim10, u16, [
+        C1: (0, ccmr1_input, ccr1),
+    ]),
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+pwm_input! {
+    TIM2: (tim2, u32),
+    TIM3: (tim3, u16),
+    TIM4: (tim4, u16),
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+hal! {
+    TIM8: (tim8, u16, [
+        C1: (0, ccmr1_input, ccr1),
+        C2: (1, ccmr1_input, ccr2),
+        C3: (2, ccmr2_input, ccr3),
+        C4: (3, ccmr2_input, ccr4),
+    ]),
+    TIM12: (tim12, u16, [
+        C1: (0, ccmr1_input, ccr1),
+        C2: (1, ccmr1_input, ccr2),
+    ]),
+    TIM13: (tim13, u16, [
+        C1: (0, ccmr1_input, ccr1),
+    ]),
+    TIM14: (tim14, u16, [
+        C1: (0, ccmr1_input, ccr1),
+    ]),
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+pwm_input! {
+    TIM8: (tim8, u16),
+    TIM12: (tim12, u16),
+}
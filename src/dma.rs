@@ -0,0 +1,855 @@
+//! Direct Memory Access
+//!
+//! Each DMA controller is split into its eight streams with [`StreamsTuple`]. A stream is
+//! then combined with a peripheral and a `'static` buffer into a [`Transfer`], which owns all
+//! three until it is released. Only the stream/channel/peripheral combinations wired up in
+//! hardware are accepted, see the `DMASet` implementations at the end of this module.
+//!
+//! ```
+//! use stm32f4xx_hal::dma::{config::DmaConfig, StreamsTuple, Transfer};
+//!
+//! let streams = StreamsTuple::new(dp.DMA2);
+//! let config = DmaConfig::default().memory_increment(true);
+//! let mut transfer = Transfer::init(streams.0, adc, buffer, config);
+//! transfer.start(|adc| adc.start_conversion());
+//! transfer.wait().unwrap();
+//! let (stream, adc, buffer) = transfer.release();
+//! ```
+
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use cast::u16;
+
+use crate::adc::Adc;
+use crate::rcc::{Enable, Reset};
+use crate::stm32::{dma2, DMA1, DMA2};
+use crate::stm32::ADC1;
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::stm32::{ADC2, ADC3};
+
+/// DMA error
+#[derive(Debug)]
+pub enum Error {
+    /// A bus error occurred while accessing memory or the peripheral
+    Transfer,
+    /// The peripheral requested data while the stream was in direct mode and not ready
+    DirectMode,
+    /// FIFO overrun or underrun
+    Fifo,
+    /// The half being processed in circular mode was overwritten by the DMA in the meantime
+    Overrun,
+    #[doc(hidden)]
+    _Extensible,
+}
+
+/// Half of a buffer used in circular mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Half {
+    /// First half of the buffer, filled when the half-transfer flag is raised
+    First,
+    /// Second half of the buffer, filled when the transfer-complete flag is raised
+    Second,
+}
+
+/// Contains types related to DMA stream configuration
+pub mod config {
+    /// Priority of a stream relative to the other streams of the same controller
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Priority {
+        /// Low
+        Low = 0,
+        /// Medium
+        Medium = 1,
+        /// High
+        High = 2,
+        /// Very high
+        VeryHigh = 3,
+    }
+
+    /// FIFO fill level at which the stream is serviced
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum FifoThreshold {
+        /// 1/4 full
+        QuarterFull = 0,
+        /// 1/2 full
+        HalfFull = 1,
+        /// 3/4 full
+        ThreeQuarterFull = 2,
+        /// Full
+        Full = 3,
+    }
+
+    /// Number of beats transferred per burst
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum BurstMode {
+        /// Single transfer
+        NoBurst = 0,
+        /// Incremental burst of 4 beats
+        Burst4 = 1,
+        /// Incremental burst of 8 beats
+        Burst8 = 2,
+        /// Incremental burst of 16 beats
+        Burst16 = 3,
+    }
+
+    /// Configuration for a DMA stream
+    #[derive(Debug, Clone, Copy)]
+    pub struct DmaConfig {
+        pub(crate) priority: Priority,
+        pub(crate) memory_increment: bool,
+        pub(crate) peripheral_increment: bool,
+        pub(crate) circular: bool,
+        pub(crate) transfer_complete_interrupt: bool,
+        pub(crate) half_transfer_interrupt: bool,
+        pub(crate) transfer_error_interrupt: bool,
+        pub(crate) direct_mode_error_interrupt: bool,
+        pub(crate) fifo_error_interrupt: bool,
+        pub(crate) fifo_enable: bool,
+        pub(crate) fifo_threshold: FifoThreshold,
+        pub(crate) memory_burst: BurstMode,
+        pub(crate) peripheral_burst: BurstMode,
+    }
+
+    impl DmaConfig {
+        /// Set the stream priority
+        pub fn priority(mut self, priority: Priority) -> Self {
+            self.priority = priority;
+            self
+        }
+        /// Increment the memory address after each transfer
+        pub fn memory_increment(mut self, memory_increment: bool) -> Self {
+            self.memory_increment = memory_increment;
+            self
+        }
+        /// Increment the peripheral address after each transfer
+        pub fn peripheral_increment(mut self, peripheral_increment: bool) -> Self {
+            self.peripheral_increment = peripheral_increment;
+            self
+        }
+        /// Restart from the beginning of the buffer once it has been transferred
+        pub fn circular(mut self, circular: bool) -> Self {
+            self.circular = circular;
+            self
+        }
+        /// Raise an interrupt when the transfer is complete
+        pub fn transfer_complete_interrupt(mut self, transfer_complete_interrupt: bool) -> Self {
+            self.transfer_complete_interrupt = transfer_complete_interrupt;
+            self
+        }
+        /// Raise an interrupt when half of the buffer has been transferred
+        pub fn half_transfer_interrupt(mut self, half_transfer_interrupt: bool) -> Self {
+            self.half_transfer_interrupt = half_transfer_interrupt;
+            self
+        }
+        /// Raise an interrupt on a transfer error
+        pub fn transfer_error_interrupt(mut self, transfer_error_interrupt: bool) -> Self {
+            self.transfer_error_interrupt = transfer_error_interrupt;
+            self
+        }
+        /// Raise an interrupt on a direct mode error
+        pub fn direct_mode_error_interrupt(mut self, direct_mode_error_interrupt: bool) -> Self {
+            self.direct_mode_error_interrupt = direct_mode_error_interrupt;
+            self
+        }
+        /// Raise an interrupt on a FIFO error
+        pub fn fifo_error_interrupt(mut self, fifo_error_interrupt: bool) -> Self {
+            self.fifo_error_interrupt = fifo_error_interrupt;
+            self
+        }
+        /// Use the FIFO instead of direct mode
+        pub fn fifo_enable(mut self, fifo_enable: bool) -> Self {
+            self.fifo_enable = fifo_enable;
+            self
+        }
+        /// Set the FIFO threshold, only used if the FIFO is enabled
+        pub fn fifo_threshold(mut self, fifo_threshold: FifoThreshold) -> Self {
+            self.fifo_threshold = fifo_threshold;
+            self
+        }
+        /// Set the memory burst size, only used if the FIFO is enabled
+        pub fn memory_burst(mut self, memory_burst: BurstMode) -> Self {
+            self.memory_burst = memory_burst;
+            self
+        }
+        /// Set the peripheral burst size, only used if the FIFO is enabled
+        pub fn peripheral_burst(mut self, peripheral_burst: BurstMode) -> Self {
+            self.peripheral_burst = peripheral_burst;
+            self
+        }
+    }
+
+    impl Default for DmaConfig {
+        fn default() -> Self {
+            Self {
+                priority: Priority::Medium,
+                memory_increment: true,
+                peripheral_increment: false,
+                circular: false,
+                transfer_complete_interrupt: false,
+                half_transfer_interrupt: false,
+                transfer_error_interrupt: false,
+                direct_mode_error_interrupt: false,
+                fifo_error_interrupt: false,
+                fifo_enable: false,
+                fifo_threshold: FifoThreshold::QuarterFull,
+                memory_burst: BurstMode::NoBurst,
+                peripheral_burst: BurstMode::NoBurst,
+            }
+        }
+    }
+}
+
+/// A DMA controller
+pub trait Instance: Deref<Target = dma2::RegisterBlock> {
+    /// Pointer to the register block
+    fn ptr() -> *const dma2::RegisterBlock;
+    /// Enables and resets the controller
+    fn enable_and_reset();
+}
+
+macro_rules! dma {
+    ($($DMAX:ident,)+) => {
+        $(
+            impl Instance for $DMAX {
+                fn ptr() -> *const dma2::RegisterBlock {
+                    $DMAX::ptr()
+                }
+
+                fn enable_and_reset() {
+                    $DMAX::enable();
+                    $DMAX::reset();
+                }
+            }
+        )+
+    };
+}
+
+dma! {
+    DMA1,
+    DMA2,
+}
+
+/// Channel selected by a stream (type state)
+pub trait Channel {
+    /// Value written to the CHSEL field
+    const VALUE: u8;
+}
+
+macro_rules! channels {
+    ($($Channel:ident: $value:expr,)+) => {
+        $(
+            /// Stream channel
+            pub struct $Channel;
+
+            impl Channel for $Channel {
+                const VALUE: u8 = $value;
+            }
+        )+
+    };
+}
+
+channels! {
+    Channel0: 0,
+    Channel1: 1,
+    Channel2: 2,
+    Channel3: 3,
+    Channel4: 4,
+    Channel5: 5,
+    Channel6: 6,
+    Channel7: 7,
+}
+
+#[cfg(any(
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+channels! {
+    Channel8: 8,
+    Channel9: 9,
+    Channel10: 10,
+    Channel11: 11,
+    Channel12: 12,
+    Channel13: 13,
+    Channel14: 14,
+    Channel15: 15,
+}
+
+/// Direction of a transfer (type state)
+pub trait Direction {
+    /// Value written to the DIR field
+    const VALUE: u8;
+}
+
+/// Data is read from the peripheral and written to memory (type state)
+pub struct PeripheralToMemory;
+
+/// Data is read from memory and written to the peripheral (type state)
+pub struct MemoryToPeripheral;
+
+impl Direction for PeripheralToMemory {
+    const VALUE: u8 = 0b00;
+}
+
+impl Direction for MemoryToPeripheral {
+    const VALUE: u8 = 0b01;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Type of a DMA data item, implemented for `u8`, `u16` and `u32`
+pub trait TransferSize: sealed::Sealed {
+    /// Value of the MSIZE/PSIZE fields
+    #[doc(hidden)]
+    const VALUE: u8;
+}
+
+impl sealed::Sealed for u8 {}
+impl TransferSize for u8 {
+    const VALUE: u8 = 0b00;
+}
+
+impl sealed::Sealed for u16 {}
+impl TransferSize for u16 {
+    const VALUE: u8 = 0b01;
+}
+
+impl sealed::Sealed for u32 {}
+impl TransferSize for u32 {
+    const VALUE: u8 = 0b10;
+}
+
+/// A peripheral register that can be the source or destination of a DMA transfer
+///
+/// # Safety
+///
+/// `address` must return the address of a register that accepts accesses of the size of
+/// `MemSize`.
+pub unsafe trait PeriAddress {
+    /// Size of a single data item
+    type MemSize: TransferSize;
+
+    /// Address of the data register
+    fn address(&self) -> u32;
+}
+
+/// Marks a stream/channel/direction combination as usable with a peripheral
+///
+/// # Safety
+///
+/// Only implement this for combinations listed in the request mapping tables of the reference
+/// manual.
+pub unsafe trait DMASet<STREAM, CHANNEL, DIRECTION> {}
+
+/// A DMA stream
+pub trait Stream {
+    /// Number of the stream in its controller
+    const NUMBER: usize;
+
+    /// Set the address of the peripheral register
+    fn set_peripheral_address(&mut self, value: u32);
+
+    /// Set the address of the memory buffer
+    fn set_memory_address(&mut self, value: u32);
+
+    /// Set the number of data items to transfer
+    fn set_number_of_transfers(&mut self, value: u16);
+
+    /// Number of data items left to transfer
+    fn number_of_transfers(&self) -> u16;
+
+    /// Enable the stream
+    ///
+    /// # Safety
+    ///
+    /// The stream must have been configured and the memory it points to must stay valid for
+    /// the whole transfer.
+    unsafe fn enable(&mut self);
+
+    /// Disable the stream and wait for any ongoing beat to finish
+    fn disable(&mut self);
+
+    /// Return true if the stream is enabled
+    fn is_enabled(&self) -> bool;
+
+    /// Clear all interrupt flags of the stream
+    fn clear_interrupts(&mut self);
+
+    /// Clear the transfer complete flag
+    fn clear_transfer_complete_interrupt(&mut self);
+
+    /// Clear the half transfer flag
+    fn clear_half_transfer_interrupt(&mut self);
+
+    /// Return true if the transfer complete flag is set
+    fn is_transfer_complete(&self) -> bool;
+
+    /// Return true if the half transfer flag is set
+    fn is_half_transfer(&self) -> bool;
+
+    /// Return the first error flag that is set, if any
+    fn error(&self) -> Option<Error>;
+
+    /// Configure the control and FIFO registers of the stream, which must be disabled
+    fn configure(&mut self, channel: u8, direction: u8, size: u8, config: &config::DmaConfig);
+}
+
+// Offsets of the stream flags in the LISR/HISR and LIFCR/HIFCR registers
+const FLAG_OFFSETS: [u32; 4] = [0, 6, 16, 22];
+const FEIF: u32 = 1 << 0;
+const DMEIF: u32 = 1 << 2;
+const TEIF: u32 = 1 << 3;
+const HTIF: u32 = 1 << 4;
+const TCIF: u32 = 1 << 5;
+const ALL_FLAGS: u32 = FEIF | DMEIF | TEIF | HTIF | TCIF;
+
+macro_rules! streams {
+    ($($Stream:ident: $number:expr,)+) => {
+        $(
+            /// DMA stream
+            pub struct $Stream<DMA> {
+                _dma: PhantomData<DMA>,
+            }
+
+            impl<DMA: Instance> $Stream<DMA> {
+                fn flags() -> u32 {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let dma = unsafe { &*DMA::ptr() };
+                    let isr = if $number < 4 {
+                        dma.lisr.read().bits()
+                    } else {
+                        dma.hisr.read().bits()
+                    };
+                    isr >> FLAG_OFFSETS[$number % 4]
+                }
+
+                fn clear_flags(flags: u32) {
+                    // NOTE(unsafe) atomic write to a stateless register
+                    let dma = unsafe { &*DMA::ptr() };
+                    let mask = flags << FLAG_OFFSETS[$number % 4];
+                    if $number < 4 {
+                        dma.lifcr.write(|w| unsafe { w.bits(mask) });
+                    } else {
+                        dma.hifcr.write(|w| unsafe { w.bits(mask) });
+                    }
+                }
+
+                fn st() -> &'static dma2::ST {
+                    // NOTE(unsafe) the stream registers are owned by this type
+                    unsafe { &(*DMA::ptr()).st[$number] }
+                }
+            }
+
+            impl<DMA: Instance> Stream for $Stream<DMA> {
+                const NUMBER: usize = $number;
+
+                fn set_peripheral_address(&mut self, value: u32) {
+                    Self::st().par.write(|w| unsafe { w.bits(value) });
+                }
+
+                fn set_memory_address(&mut self, value: u32) {
+                    Self::st().m0ar.write(|w| unsafe { w.bits(value) });
+                }
+
+                fn set_number_of_transfers(&mut self, value: u16) {
+                    Self::st().ndtr.write(|w| unsafe { w.bits(u32::from(value)) });
+                }
+
+                fn number_of_transfers(&self) -> u16 {
+                    Self::st().ndtr.read().bits() as u16
+                }
+
+                unsafe fn enable(&mut self) {
+                    Self::st().cr.modify(|_, w| w.en().set_bit());
+                }
+
+                fn disable(&mut self) {
+                    Self::st().cr.modify(|_, w| w.en().clear_bit());
+                    while self.is_enabled() {}
+                }
+
+                fn is_enabled(&self) -> bool {
+                    Self::st().cr.read().en().bit_is_set()
+                }
+
+                fn clear_interrupts(&mut self) {
+                    Self::clear_flags(ALL_FLAGS);
+                }
+
+                fn clear_transfer_complete_interrupt(&mut self) {
+                    Self::clear_flags(TCIF);
+                }
+
+                fn clear_half_transfer_interrupt(&mut self) {
+                    Self::clear_flags(HTIF);
+                }
+
+                fn is_transfer_complete(&self) -> bool {
+                    Self::flags() & TCIF != 0
+                }
+
+                fn is_half_transfer(&self) -> bool {
+                    Self::flags() & HTIF != 0
+                }
+
+                fn error(&self) -> Option<Error> {
+                    let flags = Self::flags();
+                    if flags & TEIF != 0 {
+                        Some(Error::Transfer)
+                    } else if flags & DMEIF != 0 {
+                        Some(Error::DirectMode)
+                    } else if flags & FEIF != 0 {
+                        Some(Error::Fifo)
+                    } else {
+                        None
+                    }
+                }
+
+                fn configure(
+                    &mut self,
+                    channel: u8,
+                    direction: u8,
+                    size: u8,
+                    config: &config::DmaConfig,
+                ) {
+                    let st = Self::st();
+
+                    // some chip variants declare `.bits()` as unsafe, some don't
+                    #[allow(unused_unsafe)]
+                    st.fcr.write(|w| unsafe {
+                        w.feie()
+                            .bit(config.fifo_error_interrupt)
+                            .dmdis()
+                            .bit(config.fifo_enable)
+                            .fth()
+                            .bits(config.fifo_threshold as u8)
+                    });
+
+                    #[allow(unused_unsafe)]
+                    st.cr.write(|w| unsafe {
+                        w.chsel()
+                            .bits(channel)
+                            .mburst()
+                            .bits(config.memory_burst as u8)
+                            .pburst()
+                            .bits(config.peripheral_burst as u8)
+                            .pl()
+                            .bits(config.priority as u8)
+                            .msize()
+                            .bits(size)
+                            .psize()
+                            .bits(size)
+                            .minc()
+                            .bit(config.memory_increment)
+                            .pinc()
+                            .bit(config.peripheral_increment)
+                            .circ()
+                            .bit(config.circular)
+                            .dir()
+                            .bits(direction)
+                            .tcie()
+                            .bit(config.transfer_complete_interrupt)
+                            .htie()
+                            .bit(config.half_transfer_interrupt)
+                            .teie()
+                            .bit(config.transfer_error_interrupt)
+                            .dmeie()
+                            .bit(config.direct_mode_error_interrupt)
+                    });
+
+                    // the PAC only knows the lower three CHSEL bits, CHSEL[3] selects the
+                    // channels 8 to 15
+                    #[cfg(any(
+                        feature = "stm32f413",
+                        feature = "stm32f423"
+                    ))]
+                    st.cr.modify(|r, w| unsafe {
+                        w.bits(r.bits() | (u32::from(channel >> 3) << 28))
+                    });
+                }
+            }
+        )+
+
+        /// All the streams of a DMA controller
+        pub struct StreamsTuple<DMA>(
+            $(pub $Stream<DMA>,)+
+        );
+
+        impl<DMA: Instance> StreamsTuple<DMA> {
+            /// Enables and resets the DMA controller and splits it into its streams
+            pub fn new(_regs: DMA) -> Self {
+                DMA::enable_and_reset();
+
+                StreamsTuple(
+                    $($Stream { _dma: PhantomData },)+
+                )
+            }
+        }
+    };
+}
+
+streams! {
+    Stream0: 0,
+    Stream1: 1,
+    Stream2: 2,
+    Stream3: 3,
+    Stream4: 4,
+    Stream5: 5,
+    Stream6: 6,
+    Stream7: 7,
+}
+
+/// A DMA transfer owning its stream, peripheral and buffer
+pub struct Transfer<STREAM, CHANNEL, PERIPHERAL, DIRECTION, BUF: 'static> {
+    stream: STREAM,
+    peripheral: PERIPHERAL,
+    buf: &'static mut BUF,
+    _channel: PhantomData<CHANNEL>,
+    _direction: PhantomData<DIRECTION>,
+}
+
+impl<STREAM, CHANNEL, PERIPHERAL, DIRECTION, BUF>
+    Transfer<STREAM, CHANNEL, PERIPHERAL, DIRECTION, BUF>
+where
+    STREAM: Stream,
+    CHANNEL: Channel,
+    PERIPHERAL: PeriAddress + DMASet<STREAM, CHANNEL, DIRECTION>,
+    DIRECTION: Direction,
+    BUF: AsMut<[PERIPHERAL::MemSize]>,
+{
+    /// Configures `stream` to move data between `peripheral` and `buf`
+    ///
+    /// The transfer is not started until [`start`](#method.start) is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer holds more than 65535 items.
+    pub fn init(
+        mut stream: STREAM,
+        peripheral: PERIPHERAL,
+        buf: &'static mut BUF,
+        config: config::DmaConfig,
+    ) -> Self {
+        stream.disable();
+        stream.clear_interrupts();
+
+        let (address, len) = {
+            let slice = buf.as_mut();
+            (slice.as_mut_ptr() as u32, slice.len())
+        };
+
+        stream.set_peripheral_address(peripheral.address());
+        stream.set_memory_address(address);
+        stream.set_number_of_transfers(u16(len).unwrap());
+
+        stream.configure(
+            CHANNEL::VALUE,
+            DIRECTION::VALUE,
+            <PERIPHERAL::MemSize as TransferSize>::VALUE,
+            &config,
+        );
+
+        Transfer {
+            stream,
+            peripheral,
+            buf,
+            _channel: PhantomData,
+            _direction: PhantomData,
+        }
+    }
+
+    /// Starts the transfer
+    ///
+    /// `f` is called right after the stream is enabled and is where the peripheral should be
+    /// told to start issuing DMA requests.
+    pub fn start<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut PERIPHERAL),
+    {
+        // Make sure all buffer accesses happen before the DMA takes over
+        compiler_fence(Ordering::Release);
+        // NOTE(unsafe) the stream was configured in `init` and the buffer is `'static`
+        unsafe { self.stream.enable() };
+        f(&mut self.peripheral);
+    }
+
+    /// Pauses the transfer, it can be resumed with `start`
+    pub fn pause<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut PERIPHERAL),
+    {
+        f(&mut self.peripheral);
+        self.stream.disable();
+    }
+
+    /// Return true if the whole buffer has been transferred
+    pub fn is_complete(&self) -> bool {
+        self.stream.is_transfer_complete()
+    }
+
+    /// Return true if the first half of the buffer has been transferred
+    pub fn is_half_complete(&self) -> bool {
+        self.stream.is_half_transfer()
+    }
+
+    /// Clear the transfer complete flag
+    pub fn clear_transfer_complete_interrupt(&mut self) {
+        self.stream.clear_transfer_complete_interrupt();
+    }
+
+    /// Clear the half transfer flag
+    pub fn clear_half_transfer_interrupt(&mut self) {
+        self.stream.clear_half_transfer_interrupt();
+    }
+
+    /// Clear all interrupt flags of the stream
+    pub fn clear_interrupts(&mut self) {
+        self.stream.clear_interrupts();
+    }
+
+    /// Number of data items left to transfer
+    pub fn number_of_transfers(&self) -> u16 {
+        self.stream.number_of_transfers()
+    }
+
+    /// Block until the transfer is complete or an error occurs
+    pub fn wait(&mut self) -> Result<(), Error> {
+        while !self.stream.is_transfer_complete() {
+            if let Some(error) = self.stream.error() {
+                return Err(error);
+            }
+        }
+        compiler_fence(Ordering::Acquire);
+        Ok(())
+    }
+
+    /// Gives access to the half of the buffer that the DMA has finished with in circular mode
+    ///
+    /// The half-transfer and transfer-complete flags are used to find out which half is ready,
+    /// the flag is then cleared and `f` is called on that half. `Err(Error::Overrun)` is
+    /// returned if the DMA wrapped around to the same half while `f` was running, in which
+    /// case the data seen by `f` may have been partially overwritten. If both flags are already
+    /// set, the DMA is rewriting one of the halves, so both flags are cleared and
+    /// `Err(Error::Overrun)` is returned without calling `f`. Returns
+    /// `Err(nb::Error::WouldBlock)` if neither half is ready.
+    pub fn peek<R, F>(&mut self, f: F) -> nb::Result<R, Error>
+    where
+        F: FnOnce(&[PERIPHERAL::MemSize], Half) -> R,
+    {
+        if let Some(error) = self.stream.error() {
+            return Err(nb::Error::Other(error));
+        }
+
+        let half = match (self.stream.is_half_transfer(), self.stream.is_transfer_complete()) {
+            (true, true) => {
+                self.stream.clear_half_transfer_interrupt();
+                self.stream.clear_transfer_complete_interrupt();
+                return Err(nb::Error::Other(Error::Overrun));
+            }
+            (true, false) => {
+                self.stream.clear_half_transfer_interrupt();
+                Half::First
+            }
+            (false, true) => {
+                self.stream.clear_transfer_complete_interrupt();
+                Half::Second
+            }
+            (false, false) => return Err(nb::Error::WouldBlock),
+        };
+
+        compiler_fence(Ordering::Acquire);
+        let slice = self.buf.as_mut();
+        let middle = slice.len() / 2;
+        let result = match half {
+            Half::First => f(&slice[..middle], half),
+            Half::Second => f(&slice[middle..], half),
+        };
+        compiler_fence(Ordering::Acquire);
+
+        // The half we just read is being written again once the DMA has finished the other one
+        let overrun = match half {
+            Half::First => self.stream.is_transfer_complete(),
+            Half::Second => self.stream.is_half_transfer(),
+        };
+        if overrun {
+            Err(nb::Error::Other(Error::Overrun))
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Stops the transfer and releases the stream, the peripheral and the buffer
+    pub fn release(mut self) -> (STREAM, PERIPHERAL, &'static mut BUF) {
+        self.stream.disable();
+        self.stream.clear_interrupts();
+        compiler_fence(Ordering::Acquire);
+        (self.stream, self.peripheral, self.buf)
+    }
+}
+
+macro_rules! dma_map {
+    ($(($Stream:ty, $Channel:ty, $Peripheral:ty, $Direction:ty),)+) => {
+        $(
+            unsafe impl DMASet<$Stream, $Channel, $Direction> for $Peripheral {}
+        )+
+    };
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+dma_map! {
+    (Stream0<DMA2>, Channel0, Adc<ADC1>, PeripheralToMemory),
+    (Stream4<DMA2>, Channel0, Adc<ADC1>, PeripheralToMemory),
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+dma_map! {
+    (Stream2<DMA2>, Channel1, Adc<ADC2>, PeripheralToMemory),
+    (Stream3<DMA2>, Channel1, Adc<ADC2>, PeripheralToMemory),
+    (Stream0<DMA2>, Channel2, Adc<ADC3>, PeripheralToMemory),
+    (Stream1<DMA2>, Channel2, Adc<ADC3>, PeripheralToMemory),
+}
@@ -0,0 +1,197 @@
+//! External interrupt / event controller
+//!
+//! Lines 0 to 15 are connected to the GPIO pins, see `gpio::ExtiPin`, the higher lines to the
+//! internal `Event`s.
+
+use cortex_m::interrupt;
+
+use crate::stm32::exti::RegisterBlock;
+
+/// Edge
+#[derive(Debug, PartialEq)]
+pub enum Edge {
+    RISING,
+    FALLING,
+    RISING_FALLING,
+}
+
+/// Line of the EXTI controller
+pub trait ExtiLine {
+    /// Number of the line
+    fn line(&self) -> u8;
+}
+
+/// EXTI line connected to the GPIO pins with the given number
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GpioLine(u8);
+
+impl GpioLine {
+    /// # Panics
+    ///
+    /// If `i` isn't a pin number from 0 to 15
+    pub fn new(i: u8) -> Self {
+        assert!(i < 16);
+        GpioLine(i)
+    }
+}
+
+impl ExtiLine for GpioLine {
+    fn line(&self) -> u8 {
+        self.0
+    }
+}
+
+/// EXTI lines connected to the internal events
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// PVD output
+    Pvd = 16,
+    /// RTC Alarm event
+    RtcAlarm = 17,
+    #[cfg(any(
+        feature = "stm32f401",
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f415",
+        feature = "stm32f417",
+        feature = "stm32f423",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    /// USB OTG FS wakeup event
+    OtgFsWakeup = 18,
+    #[cfg(any(
+        feature = "stm32f407",
+        feature = "stm32f417",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    /// Ethernet wakeup event
+    EthernetWakeup = 19,
+    #[cfg(any(
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f415",
+        feature = "stm32f417",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    /// USB OTG HS wakeup event
+    OtgHsWakeup = 20,
+    /// RTC Tamper and TimeStamp events
+    RtcTamperTimestamp = 21,
+    /// RTC Wakeup event
+    RtcWakeup = 22,
+    #[cfg(any(
+        feature = "stm32f410",
+        feature = "stm32f413",
+        feature = "stm32f423"
+    ))]
+    /// LPTIM1 asynchronous event
+    Lptim1 = 23,
+}
+
+impl ExtiLine for Event {
+    fn line(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Extension trait to configure the EXTI lines
+///
+/// Implemented for the register block, so the methods can be called on `EXTI`.
+pub trait ExtiExt {
+    /// Selects the edges that trigger the line
+    fn trigger_on_edge<L: ExtiLine>(&self, line: L, edge: Edge);
+
+    /// Unmasks the interrupt request of the line
+    fn listen<L: ExtiLine>(&self, line: L);
+
+    /// Masks the interrupt request of the line
+    fn unlisten<L: ExtiLine>(&self, line: L);
+
+    /// Unmasks the event request of the line, for waking up from `WFE`
+    fn listen_event<L: ExtiLine>(&self, line: L);
+
+    /// Masks the event request of the line
+    fn unlisten_event<L: ExtiLine>(&self, line: L);
+
+    /// Triggers the line from software
+    fn generate_software_interrupt<L: ExtiLine>(&self, line: L);
+
+    /// Returns whether the line has a pending request
+    fn is_pending<L: ExtiLine>(&self, line: L) -> bool;
+
+    /// Clears the pending request of the line
+    fn clear_pending<L: ExtiLine>(&self, line: L);
+}
+
+impl ExtiExt for RegisterBlock {
+    fn trigger_on_edge<L: ExtiLine>(&self, line: L, edge: Edge) {
+        let mask = 1 << line.line();
+        let (rising, falling) = match edge {
+            Edge::RISING => (true, false),
+            Edge::FALLING => (false, true),
+            Edge::RISING_FALLING => (true, true),
+        };
+        interrupt::free(|_| {
+            self.rtsr.modify(|r, w| unsafe {
+                w.bits(if rising { r.bits() | mask } else { r.bits() & !mask })
+            });
+            self.ftsr.modify(|r, w| unsafe {
+                w.bits(if falling { r.bits() | mask } else { r.bits() & !mask })
+            });
+        });
+    }
+
+    fn listen<L: ExtiLine>(&self, line: L) {
+        let mask = 1 << line.line();
+        interrupt::free(|_| self.imr.modify(|r, w| unsafe { w.bits(r.bits() | mask) }));
+    }
+
+    fn unlisten<L: ExtiLine>(&self, line: L) {
+        let mask = 1 << line.line();
+        interrupt::free(|_| self.imr.modify(|r, w| unsafe { w.bits(r.bits() & !mask) }));
+    }
+
+    fn listen_event<L: ExtiLine>(&self, line: L) {
+        let mask = 1 << line.line();
+        interrupt::free(|_| self.emr.modify(|r, w| unsafe { w.bits(r.bits() | mask) }));
+    }
+
+    fn unlisten_event<L: ExtiLine>(&self, line: L) {
+        let mask = 1 << line.line();
+        interrupt::free(|_| self.emr.modify(|r, w| unsafe { w.bits(r.bits() & !mask) }));
+    }
+
+    fn generate_software_interrupt<L: ExtiLine>(&self, line: L) {
+        let mask = 1 << line.line();
+        interrupt::free(|_| self.swier.modify(|r, w| unsafe { w.bits(r.bits() | mask) }));
+    }
+
+    fn is_pending<L: ExtiLine>(&self, line: L) -> bool {
+        self.pr.read().bits() & (1 << line.line()) != 0
+    }
+
+    fn clear_pending<L: ExtiLine>(&self, line: L) {
+        // NOTE(unsafe) atomic write, the bits are cleared by writing 1
+        self.pr.write(|w| unsafe { w.bits(1 << line.line()) });
+    }
+}
@@ -1,8 +1,12 @@
 //! General Purpose Input / Output
 
+use core::convert::Infallible;
 use core::marker::PhantomData;
 
-use crate::stm32::{EXTI, SYSCFG};
+use embedded_hal::digital::v2::{toggleable, InputPin, OutputPin, StatefulOutputPin};
+
+use crate::exti::{ExtiExt, GpioLine};
+use crate::stm32::{gpioa, EXTI, GPIOA, SYSCFG};
 
 /// Extension trait to split a GPIO peripheral in independent pins and registers
 pub trait GpioExt {
@@ -70,13 +74,20 @@ pub enum Speed {
     VeryHigh = 3,
 }
 
-#[derive(Debug, PartialEq)]
-pub enum Edge {
-    RISING,
-    FALLING,
-    RISING_FALLING,
+/// Internal pull-up / pull-down selection
+///
+/// `set_pull` is available on outputs and alternate function pins. Inputs select their pull
+/// resistor with `into_pull_up_input` and friends so that their type follows, analog pins need
+/// it off.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pull {
+    None = 0,
+    Up = 1,
+    Down = 2,
 }
 
+pub use crate::exti::Edge;
+
 /// External Interrupt Pin
 pub trait ExtiPin {
     fn make_interrupt_source(&mut self, syscfg: &mut SYSCFG);
@@ -86,8 +97,510 @@ pub trait ExtiPin {
     fn clear_interrupt_pending_bit(&mut self);
 }
 
+/// Fully erased pin
+///
+/// Keeps the port and the pin number at runtime, so pins of different ports can be collected
+/// into an array
+pub struct ErasedPin<MODE> {
+    // port number in the upper, pin number in the lower 4 bits
+    pin_port: u8,
+    _mode: PhantomData<MODE>,
+}
+
+impl<MODE> ErasedPin<MODE> {
+    fn new(port: u8, i: u8) -> Self {
+        ErasedPin {
+            pin_port: port << 4 | i,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Returns the pin number within its port
+    pub fn get_id(&self) -> u8 {
+        self.pin_port & 0x0f
+    }
+
+    /// Returns the port number, 0 for GPIOA, 1 for GPIOB and so on
+    pub fn get_port_id(&self) -> u8 {
+        self.pin_port >> 4
+    }
+
+    /// Set pin speed
+    pub fn set_speed(self, speed: Speed) -> Self {
+        set_speed(self.get_port_id(), self.get_id(), speed);
+        self
+    }
+
+    fn block(&self) -> *const gpioa::RegisterBlock {
+        port_block(self.get_port_id())
+    }
+}
+
+impl<MODE> ErasedPin<Output<MODE>> {
+    /// Set the internal pull-up / pull-down
+    pub fn set_pull(self, pull: Pull) -> Self {
+        set_pull(self.get_port_id(), self.get_id(), pull);
+        self
+    }
+
+    /// Turns the output into an open drain output
+    pub fn set_open_drain(self) -> ErasedPin<Output<OpenDrain>> {
+        set_open_drain(self.get_port_id(), self.get_id());
+        ErasedPin::new(self.get_port_id(), self.get_id())
+    }
+}
+
+impl<MODE> ErasedPin<Alternate<MODE>> {
+    /// Set the internal pull-up / pull-down
+    pub fn set_pull(self, pull: Pull) -> Self {
+        set_pull(self.get_port_id(), self.get_id(), pull);
+        self
+    }
+
+    /// Turns pin alternate configuration pin into open drain
+    pub fn set_open_drain(self) -> Self {
+        set_open_drain(self.get_port_id(), self.get_id());
+        self
+    }
+}
+
+/// Returns the register block of the port with the given number
+fn port_block(port: u8) -> *const gpioa::RegisterBlock {
+    // The GPIO ports are 0x400 apart and share the same register layout
+    (GPIOA::ptr() as usize + 0x400 * usize::from(port)) as *const _
+}
+
+fn set_speed(port: u8, i: u8, speed: Speed) {
+    let offset = 2 * i;
+    // NOTE(unsafe) the pin only modifies its own bits
+    unsafe {
+        (*port_block(port)).ospeedr.modify(|r, w| {
+            w.bits((r.bits() & !(0b11 << offset)) | ((speed as u32) << offset))
+        })
+    };
+}
+
+fn set_pull(port: u8, i: u8, pull: Pull) {
+    let offset = 2 * i;
+    // NOTE(unsafe) the pin only modifies its own bits
+    unsafe {
+        (*port_block(port)).pupdr.modify(|r, w| {
+            w.bits((r.bits() & !(0b11 << offset)) | ((pull as u32) << offset))
+        })
+    };
+}
+
+fn set_open_drain(port: u8, i: u8) {
+    // NOTE(unsafe) the pin only modifies its own bit
+    unsafe { (*port_block(port)).otyper.modify(|r, w| w.bits(r.bits() | (1 << i))) };
+}
+
+impl<MODE> OutputPin for ErasedPin<Output<MODE>> {
+    type Error = Infallible;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        // NOTE(unsafe) atomic write to a stateless register
+        unsafe { (*self.block()).bsrr.write(|w| w.bits(1 << self.get_id())) };
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        // NOTE(unsafe) atomic write to a stateless register
+        unsafe { (*self.block()).bsrr.write(|w| w.bits(1 << (self.get_id() + 16))) };
+        Ok(())
+    }
+}
+
+impl<MODE> StatefulOutputPin for ErasedPin<Output<MODE>> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        self.is_set_low().map(|v| !v)
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(unsafe { (*self.block()).odr.read().bits() & (1 << self.get_id()) == 0 })
+    }
+}
+
+impl<MODE> toggleable::Default for ErasedPin<Output<MODE>> {}
+
+impl<MODE> InputPin for ErasedPin<Output<MODE>> {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.is_low().map(|v| !v)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(unsafe { (*self.block()).idr.read().bits() & (1 << self.get_id()) == 0 })
+    }
+}
+
+impl<MODE> InputPin for ErasedPin<Input<MODE>> {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.is_low().map(|v| !v)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(unsafe { (*self.block()).idr.read().bits() & (1 << self.get_id()) == 0 })
+    }
+}
+
+impl<MODE> ExtiPin for ErasedPin<Input<MODE>> {
+    /// Make corresponding EXTI line sensitive to this pin
+    fn make_interrupt_source(&mut self, syscfg: &mut SYSCFG) {
+        let i = self.get_id();
+        let port = u32::from(self.get_port_id());
+        let offset = 4 * (i % 4);
+        match i {
+            0..=3 => {
+                syscfg.exticr1.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xf << offset)) | (port << offset))
+                });
+            },
+            4..=7 => {
+                syscfg.exticr2.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xf << offset)) | (port << offset))
+                });
+            },
+            8..=11 => {
+                syscfg.exticr3.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xf << offset)) | (port << offset))
+                });
+            },
+            12..=15 => {
+                syscfg.exticr4.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0xf << offset)) | (port << offset))
+                });
+            },
+            _ => {}
+        }
+    }
+
+    /// Generate interrupt on rising edge, falling edge or both
+    fn trigger_on_edge(&mut self, exti: &mut EXTI, edge: Edge) {
+        exti.trigger_on_edge(GpioLine::new(self.get_id()), edge);
+    }
+
+    /// Enable external interrupts from this pin.
+    fn enable_interrupt(&mut self, exti: &mut EXTI) {
+        exti.listen(GpioLine::new(self.get_id()));
+    }
+
+    /// Disable external interrupts from this pin
+    fn disable_interrupt(&mut self, exti: &mut EXTI) {
+        exti.unlisten(GpioLine::new(self.get_id()));
+    }
+
+    /// Clear the interrupt pending bit for this pin
+    fn clear_interrupt_pending_bit(&mut self) {
+        // NOTE(unsafe) atomic write to a write-1-to-clear register
+        unsafe { (*EXTI::ptr()).clear_pending(GpioLine::new(self.get_id())) };
+    }
+}
+
+/// Pin whose configuration is locked until the next reset
+///
+/// Returned by `lock()` and `PortLock::add`. The pin can only be read and written, its mode,
+/// speed and pull resistors can't be changed anymore.
+pub struct LockedPin<PIN> {
+    pin: PIN,
+}
+
+impl<PIN: OutputPin> OutputPin for LockedPin<PIN> {
+    type Error = PIN::Error;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high()
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low()
+    }
+}
+
+impl<PIN: StatefulOutputPin> StatefulOutputPin for LockedPin<PIN> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        self.pin.is_set_high()
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        self.pin.is_set_low()
+    }
+}
+
+impl<PIN: toggleable::Default> toggleable::Default for LockedPin<PIN> {}
+
+impl<PIN: InputPin> InputPin for LockedPin<PIN> {
+    type Error = PIN::Error;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.pin.is_high()
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.pin.is_low()
+    }
+}
+
+impl<PIN: ExtiPin> ExtiPin for LockedPin<PIN> {
+    fn make_interrupt_source(&mut self, syscfg: &mut SYSCFG) {
+        self.pin.make_interrupt_source(syscfg)
+    }
+
+    fn trigger_on_edge(&mut self, exti: &mut EXTI, level: Edge) {
+        self.pin.trigger_on_edge(exti, level)
+    }
+
+    fn enable_interrupt(&mut self, exti: &mut EXTI) {
+        self.pin.enable_interrupt(exti)
+    }
+
+    fn disable_interrupt(&mut self, exti: &mut EXTI) {
+        self.pin.disable_interrupt(exti)
+    }
+
+    fn clear_interrupt_pending_bit(&mut self) {
+        self.pin.clear_interrupt_pending_bit()
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Pins and pin groups that can be added to the `PortLock` of their port
+pub trait Lockable: sealed::Sealed {
+    #[doc(hidden)]
+    type Port;
+    #[doc(hidden)]
+    fn lock_mask(&self) -> u16;
+}
+
+/// Error returned by `PortLock::lock` if the configuration of the port is already locked
+#[derive(Debug, PartialEq)]
+pub struct AlreadyLocked;
+
+/// Applies the LCKR key sequence to the pins in `mask`, returns whether they are locked
+///
+/// LCKR is frozen after the first lock sequence, so later sequences on the same port fail.
+fn lock_pins(port: u8, mask: u16) -> bool {
+    const LCKK: u32 = 1 << 16;
+    let block = port_block(port);
+    let mask = u32::from(mask);
+    // NOTE(unsafe) the key sequence must not be interrupted by another write to LCKR
+    cortex_m::interrupt::free(|_| unsafe {
+        (*block).lckr.write(|w| w.bits(LCKK | mask));
+        (*block).lckr.write(|w| w.bits(mask));
+        (*block).lckr.write(|w| w.bits(LCKK | mask));
+        (*block).lckr.read();
+        (*block).lckr.read().bits() & (LCKK | mask) == LCKK | mask
+    })
+}
+
+/// Mode of a `DynamicPin`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dynamic {
+    /// Floating input
+    InputFloating,
+    /// Input with the internal pull-up resistor
+    InputPullUp,
+    /// Input with the internal pull-down resistor
+    InputPullDown,
+    /// Push pull output
+    OutputPushPull,
+    /// Open drain output
+    OutputOpenDrain,
+    /// Analog input, can't be read or written
+    Analog,
+}
+
+impl Dynamic {
+    fn is_input(self) -> bool {
+        match self {
+            Dynamic::Analog => false,
+            _ => true,
+        }
+    }
+
+    fn is_output(self) -> bool {
+        match self {
+            Dynamic::OutputPushPull | Dynamic::OutputOpenDrain => true,
+            _ => false,
+        }
+    }
+}
+
+/// Error returned when a `DynamicPin` is used in a mode that doesn't support the operation
+#[derive(Debug, PartialEq)]
+pub enum PinModeError {
+    IncorrectMode,
+}
+
+/// Pin whose mode can be changed at runtime
+///
+/// Useful for bidirectional protocols like 1-Wire. Reading fails in analog mode, writing
+/// fails unless the pin is an output.
+pub struct DynamicPin {
+    // port number in the upper, pin number in the lower 4 bits
+    pin_port: u8,
+    mode: Dynamic,
+}
+
+impl DynamicPin {
+    fn new(port: u8, i: u8) -> Self {
+        let mut pin = DynamicPin {
+            pin_port: port << 4 | i,
+            mode: Dynamic::InputFloating,
+        };
+        pin.make_floating_input();
+        pin
+    }
+
+    /// Returns the pin number within its port
+    pub fn get_id(&self) -> u8 {
+        self.pin_port & 0x0f
+    }
+
+    /// Returns the port number, 0 for GPIOA, 1 for GPIOB and so on
+    pub fn get_port_id(&self) -> u8 {
+        self.pin_port >> 4
+    }
+
+    /// Returns the current mode of the pin
+    pub fn mode(&self) -> Dynamic {
+        self.mode
+    }
+
+    /// Configures the pin to operate as a floating input pin
+    pub fn make_floating_input(&mut self) {
+        self.set_mode(Dynamic::InputFloating);
+    }
+
+    /// Configures the pin to operate as a pulled up input pin
+    pub fn make_pull_up_input(&mut self) {
+        self.set_mode(Dynamic::InputPullUp);
+    }
+
+    /// Configures the pin to operate as a pulled down input pin
+    pub fn make_pull_down_input(&mut self) {
+        self.set_mode(Dynamic::InputPullDown);
+    }
+
+    /// Configures the pin to operate as a push pull output pin
+    pub fn make_push_pull_output(&mut self) {
+        self.set_mode(Dynamic::OutputPushPull);
+    }
+
+    /// Configures the pin to operate as an open drain output pin
+    pub fn make_open_drain_output(&mut self) {
+        self.set_mode(Dynamic::OutputOpenDrain);
+    }
+
+    /// Configures the pin to operate as an analog input pin
+    pub fn make_analog(&mut self) {
+        self.set_mode(Dynamic::Analog);
+    }
+
+    fn set_mode(&mut self, mode: Dynamic) {
+        let i = self.get_id();
+        let offset = 2 * i;
+        let (moder, pupdr) = match mode {
+            Dynamic::InputFloating => (0b00, 0b00),
+            Dynamic::InputPullUp => (0b00, 0b01),
+            Dynamic::InputPullDown => (0b00, 0b10),
+            Dynamic::OutputPushPull | Dynamic::OutputOpenDrain => (0b01, 0b00),
+            Dynamic::Analog => (0b11, 0b00),
+        };
+        let block = port_block(self.get_port_id());
+        // NOTE(unsafe) the pin only modifies its own bits. Like the mode changes of the typed
+        // pins, the read-modify-writes aren't atomic, so no other pin of the port may be
+        // reconfigured from an interrupt handler meanwhile
+        unsafe {
+            (*block).pupdr.modify(|r, w| {
+                w.bits((r.bits() & !(0b11 << offset)) | (pupdr << offset))
+            });
+            match mode {
+                Dynamic::OutputPushPull => {
+                    (*block).otyper.modify(|r, w| w.bits(r.bits() & !(0b1 << i)));
+                },
+                Dynamic::OutputOpenDrain => {
+                    (*block).otyper.modify(|r, w| w.bits(r.bits() | (0b1 << i)));
+                },
+                _ => {}
+            }
+            (*block).moder.modify(|r, w| {
+                w.bits((r.bits() & !(0b11 << offset)) | (moder << offset))
+            });
+        }
+        self.mode = mode;
+    }
+}
+
+impl OutputPin for DynamicPin {
+    type Error = PinModeError;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        if !self.mode.is_output() {
+            return Err(PinModeError::IncorrectMode);
+        }
+        // NOTE(unsafe) atomic write to a stateless register
+        unsafe { (*port_block(self.get_port_id())).bsrr.write(|w| w.bits(1 << self.get_id())) };
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        if !self.mode.is_output() {
+            return Err(PinModeError::IncorrectMode);
+        }
+        // NOTE(unsafe) atomic write to a stateless register
+        unsafe {
+            (*port_block(self.get_port_id())).bsrr.write(|w| w.bits(1 << (self.get_id() + 16)))
+        };
+        Ok(())
+    }
+}
+
+impl StatefulOutputPin for DynamicPin {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        self.is_set_low().map(|v| !v)
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        if !self.mode.is_output() {
+            return Err(PinModeError::IncorrectMode);
+        }
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(unsafe {
+            (*port_block(self.get_port_id())).odr.read().bits() & (1 << self.get_id()) == 0
+        })
+    }
+}
+
+impl InputPin for DynamicPin {
+    type Error = PinModeError;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.is_low().map(|v| !v)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        if !self.mode.is_input() {
+            return Err(PinModeError::IncorrectMode);
+        }
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(unsafe {
+            (*port_block(self.get_port_id())).idr.read().bits() & (1 << self.get_id()) == 0
+        })
+    }
+}
+
 macro_rules! gpio {
-    ($GPIOX:ident, $gpiox:ident, $iopxenr:ident, $PXx:ident, $extigpionr:expr, [
+    ($GPIOX:ident, $gpiox:ident, $PXx:ident, $extigpionr:expr, [
         $($PXi:ident: ($pxi:ident, $i:expr, $MODE:ty, $exticri:ident),)+
     ]) => {
         /// GPIO
@@ -98,11 +611,14 @@ macro_rules! gpio {
             use embedded_hal::digital::v2::{InputPin, OutputPin, StatefulOutputPin, toggleable};
             use crate::stm32::$GPIOX;
 
-            use crate::stm32::{RCC, EXTI, SYSCFG};
+            use crate::rcc::Enable;
+            use crate::stm32::{EXTI, SYSCFG};
             use super::{
                 Alternate, Floating, GpioExt, Input, OpenDrain, Output, Speed,
                 PullDown, PullUp, PushPull, AF0, AF1, AF2, AF3, AF4, AF5, AF6, AF7, AF8, AF9, AF10,
-                AF11, AF12, AF13, AF14, AF15, Analog, Edge, ExtiPin,
+                AF11, AF12, AF13, AF14, AF15, Analog, DynamicPin, Edge, ErasedPin, ExtiPin,
+                LockedPin, Pull, ExtiExt, GpioLine, lock_pins, set_open_drain, set_pull, set_speed,
+                sealed, AlreadyLocked, Lockable,
             };
 
             /// GPIO parts
@@ -111,20 +627,52 @@ macro_rules! gpio {
                     /// Pin
                     pub $pxi: $PXi<$MODE>,
                 )+
+                /// Locks the configuration of several pins of the port at once
+                pub lock: PortLock,
             }
 
             impl GpioExt for $GPIOX {
                 type Parts = Parts;
 
                 fn split(self) -> Parts {
-                    // NOTE(unsafe) This executes only during initialisation
-                    let rcc = unsafe { &(*RCC::ptr()) };
-                    rcc.ahb1enr.modify(|_, w| w.$iopxenr().set_bit());
+                    $GPIOX::enable();
 
                     Parts {
                         $(
                             $pxi: $PXi { _mode: PhantomData },
                         )+
+                        lock: PortLock { mask: 0 },
+                    }
+                }
+            }
+
+            /// Locks the configuration of several pins of this port with a single key sequence
+            ///
+            /// LCKR is frozen until the next reset once a key sequence ran, so all pins of the
+            /// port that should be locked have to be added before calling `lock`. Pins of any
+            /// mode and `PinGroup`s can be mixed.
+            pub struct PortLock {
+                mask: u16,
+            }
+
+            impl PortLock {
+                /// Adds `pin` to the pins to lock, its configuration can't be changed anymore
+                pub fn add<PIN>(&mut self, pin: PIN) -> LockedPin<PIN>
+                where
+                    PIN: Lockable<Port = $GPIOX>,
+                {
+                    self.mask |= pin.lock_mask();
+                    LockedPin { pin }
+                }
+
+                /// Locks the configuration of the added pins until the next reset
+                ///
+                /// Fails if the port is already locked, e.g. by `lock()` of a single pin.
+                pub fn lock(self) -> Result<(), AlreadyLocked> {
+                    if self.mask == 0 || lock_pins($extigpionr, self.mask) {
+                        Ok(())
+                    } else {
+                        Err(AlreadyLocked)
                     }
                 }
             }
@@ -139,6 +687,45 @@ macro_rules! gpio {
                 pub fn get_id(&self) -> u8 {
                     self.i
                 }
+
+                /// Erases the port from the type
+                pub fn erase(self) -> ErasedPin<MODE> {
+                    ErasedPin::new($extigpionr, self.i)
+                }
+
+                /// Set pin speed
+                pub fn set_speed(self, speed: Speed) -> Self {
+                    set_speed($extigpionr, self.i, speed);
+                    self
+                }
+            }
+
+            impl<MODE> $PXx<Output<MODE>> {
+                /// Set the internal pull-up / pull-down
+                pub fn set_pull(self, pull: Pull) -> Self {
+                    set_pull($extigpionr, self.i, pull);
+                    self
+                }
+
+                /// Turns the output into an open drain output
+                pub fn set_open_drain(self) -> $PXx<Output<OpenDrain>> {
+                    set_open_drain($extigpionr, self.i);
+                    $PXx { i: self.i, _mode: PhantomData }
+                }
+            }
+
+            impl<MODE> $PXx<Alternate<MODE>> {
+                /// Set the internal pull-up / pull-down
+                pub fn set_pull(self, pull: Pull) -> Self {
+                    set_pull($extigpionr, self.i, pull);
+                    self
+                }
+
+                /// Turns pin alternate configuration pin into open drain
+                pub fn set_open_drain(self) -> Self {
+                    set_open_drain($extigpionr, self.i);
+                    self
+                }
             }
 
             impl<MODE> OutputPin for $PXx<Output<MODE>> {
@@ -227,35 +814,228 @@ macro_rules! gpio {
 
                 /// Generate interrupt on rising edge, falling edge or both
                 fn trigger_on_edge(&mut self, exti: &mut EXTI, edge: Edge) {
-                    match edge {
-                        Edge::RISING => {
-                            exti.rtsr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << self.i)) });
-                            exti.ftsr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << self.i)) });
-                        },
-                        Edge::FALLING => {
-                            exti.ftsr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << self.i)) });
-                            exti.rtsr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << self.i)) });
-                        },
-                        Edge::RISING_FALLING => {
-                            exti.rtsr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << self.i)) });
-                            exti.ftsr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << self.i)) });
-                        }
-                    }
+                    exti.trigger_on_edge(GpioLine::new(self.i), edge);
                 }
 
                 /// Enable external interrupts from this pin.
                 fn enable_interrupt(&mut self, exti: &mut EXTI) {
-                    exti.imr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << self.i)) });
+                    exti.listen(GpioLine::new(self.i));
                 }
 
                 /// Disable external interrupts from this pin
                 fn disable_interrupt(&mut self, exti: &mut EXTI) {
-                    exti.imr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << self.i)) });
+                    exti.unlisten(GpioLine::new(self.i));
                 }
 
                 /// Clear the interrupt pending bit for this pin
                 fn clear_interrupt_pending_bit(&mut self) {
-                    unsafe { (*EXTI::ptr()).pr.write(|w| w.bits(1 << self.i) ) };
+                    // NOTE(unsafe) atomic write to a write-1-to-clear register
+                    unsafe { (*EXTI::ptr()).clear_pending(GpioLine::new(self.i)) };
+                }
+            }
+
+            impl<MODE> sealed::Sealed for $PXx<MODE> {}
+
+            impl<MODE> Lockable for $PXx<MODE> {
+                type Port = $GPIOX;
+
+                fn lock_mask(&self) -> u16 {
+                    1 << self.i
+                }
+            }
+
+            /// Group of up to 16 pins of this port, written and read with a single register
+            /// access
+            ///
+            /// Bit `n` of the bus value maps to the `n`th pin of the group, e.g. to drive the data
+            /// lines of a parallel bus.
+            pub struct PinGroup<MODE> {
+                pins: [u8; 16],
+                len: u8,
+                mask: u16,
+                // first pin if the pins are consecutive and in ascending order
+                shift: Option<u8>,
+                _mode: PhantomData<MODE>,
+            }
+
+            impl<MODE> PinGroup<MODE> {
+                /// Groups the given pins, the first pin is bit 0 of the bus value
+                ///
+                /// # Panics
+                ///
+                /// If more than 16 pins are given
+                pub fn new<I>(pins: I) -> Self
+                where
+                    I: IntoIterator<Item = $PXx<MODE>>,
+                {
+                    let mut group = PinGroup {
+                        pins: [0; 16],
+                        len: 0,
+                        mask: 0,
+                        shift: None,
+                        _mode: PhantomData,
+                    };
+                    for pin in pins {
+                        assert!(group.len < 16);
+                        group.pins[usize::from(group.len)] = pin.i;
+                        group.mask |= 1 << pin.i;
+                        group.len += 1;
+                    }
+                    let first = group.pins[0];
+                    let pins = &group.pins;
+                    if group.len > 0 && (0..group.len).all(|n| pins[usize::from(n)] == first + n) {
+                        group.shift = Some(first);
+                    }
+                    group
+                }
+
+                /// Mask of the port pins in the group
+                pub fn mask(&self) -> u16 {
+                    self.mask
+                }
+
+                /// Locks the configuration of all pins of the group until the next reset
+                ///
+                /// Only the first lock sequence of a port takes effect, add the group to the
+                /// port's `PortLock` to lock it together with other pins. Returns the group back
+                /// if the port is already locked.
+                pub fn lock(self) -> Result<LockedPin<Self>, Self> {
+                    if lock_pins($extigpionr, self.mask) {
+                        Ok(LockedPin { pin: self })
+                    } else {
+                        Err(self)
+                    }
+                }
+
+                /// Splits the group back into its pins
+                pub fn release(self) -> impl Iterator<Item = $PXx<MODE>> {
+                    let pins = self.pins;
+                    (0..usize::from(self.len)).map(move |n| $PXx {
+                        i: pins[n],
+                        _mode: PhantomData,
+                    })
+                }
+
+                fn port_bits(&self, value: u16) -> u16 {
+                    match self.shift {
+                        Some(shift) => (value << shift) & self.mask,
+                        None => {
+                            let mut bits = 0;
+                            for n in 0..usize::from(self.len) {
+                                if value & (1 << n) != 0 {
+                                    bits |= 1 << self.pins[n];
+                                }
+                            }
+                            bits
+                        }
+                    }
+                }
+
+                fn bus_value(&self, bits: u16) -> u16 {
+                    match self.shift {
+                        Some(shift) => (bits & self.mask) >> shift,
+                        None => {
+                            let mut value = 0;
+                            for n in 0..usize::from(self.len) {
+                                if bits & (1 << self.pins[n]) != 0 {
+                                    value |= 1 << n;
+                                }
+                            }
+                            value
+                        }
+                    }
+                }
+
+                fn read_idr(&self) -> u16 {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let bits = unsafe { (*$GPIOX::ptr()).idr.read().bits() } as u16;
+                    self.bus_value(bits)
+                }
+            }
+
+            impl<MODE> PinGroup<Output<MODE>> {
+                /// Drives all pins of the group at once, bit `n` of `value` to the `n`th pin
+                pub fn write(&mut self, value: u16) {
+                    let set = self.port_bits(value);
+                    let reset = self.mask & !set;
+                    let bits = u32::from(reset) << 16 | u32::from(set);
+                    // NOTE(unsafe) atomic write to a stateless register
+                    unsafe { (*$GPIOX::ptr()).bsrr.write(|w| w.bits(bits)) };
+                }
+
+                /// Sets all pins of the group high
+                pub fn set_high(&mut self) {
+                    // NOTE(unsafe) atomic write to a stateless register
+                    unsafe { (*$GPIOX::ptr()).bsrr.write(|w| w.bits(u32::from(self.mask))) };
+                }
+
+                /// Sets all pins of the group low
+                pub fn set_low(&mut self) {
+                    // NOTE(unsafe) atomic write to a stateless register
+                    unsafe { (*$GPIOX::ptr()).bsrr.write(|w| w.bits(u32::from(self.mask) << 16)) };
+                }
+
+                /// Returns the value the group is driving
+                pub fn read_output(&self) -> u16 {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let bits = unsafe { (*$GPIOX::ptr()).odr.read().bits() } as u16;
+                    self.bus_value(bits)
+                }
+
+                /// Reads the input levels of the pins
+                pub fn read(&self) -> u16 {
+                    self.read_idr()
+                }
+            }
+
+            impl<MODE> PinGroup<Input<MODE>> {
+                /// Reads all pins of the group at once, the `n`th pin to bit `n`
+                pub fn read(&self) -> u16 {
+                    self.read_idr()
+                }
+            }
+
+            impl<MODE> sealed::Sealed for PinGroup<MODE> {}
+
+            impl<MODE> Lockable for PinGroup<MODE> {
+                type Port = $GPIOX;
+
+                fn lock_mask(&self) -> u16 {
+                    self.mask
+                }
+            }
+
+            impl<MODE> LockedPin<PinGroup<Output<MODE>>> {
+                /// Drives all pins of the group at once, see `PinGroup::write`
+                pub fn write(&mut self, value: u16) {
+                    self.pin.write(value)
+                }
+
+                /// Sets all pins of the group high
+                pub fn set_high(&mut self) {
+                    self.pin.set_high()
+                }
+
+                /// Sets all pins of the group low
+                pub fn set_low(&mut self) {
+                    self.pin.set_low()
+                }
+
+                /// Returns the value the group is driving
+                pub fn read_output(&self) -> u16 {
+                    self.pin.read_output()
+                }
+
+                /// Reads the input levels of the pins
+                pub fn read(&self) -> u16 {
+                    self.pin.read()
+                }
+            }
+
+            impl<MODE> LockedPin<PinGroup<Input<MODE>>> {
+                /// Reads all pins of the group at once, the `n`th pin to bit `n`
+                pub fn read(&self) -> u16 {
+                    self.pin.read()
                 }
             }
 
@@ -482,72 +1262,52 @@ macro_rules! gpio {
                     }
                 }
 
-                impl<MODE> $PXi<Output<MODE>> {
+                impl<MODE> $PXi<MODE> {
                     /// Set pin speed
                     pub fn set_speed(self, speed: Speed) -> Self {
-                        let offset = 2 * $i;
-
-                        unsafe {
-                            &(*$GPIOX::ptr()).ospeedr.modify(|r, w| {
-                                w.bits((r.bits() & !(0b11 << offset)) | ((speed as u32) << offset))
-                            })
-                        };
+                        set_speed($extigpionr, $i, speed);
+                        self
+                    }
+                }
 
+                impl<MODE> $PXi<Output<MODE>> {
+                    /// Set the internal pull-up / pull-down
+                    pub fn set_pull(self, pull: Pull) -> Self {
+                        set_pull($extigpionr, $i, pull);
                         self
                     }
+
+                    /// Turns the output into an open drain output
+                    pub fn set_open_drain(self) -> $PXi<Output<OpenDrain>> {
+                        set_open_drain($extigpionr, $i);
+                        $PXi { _mode: PhantomData }
+                    }
                 }
 
                 impl $PXi<Output<OpenDrain>> {
                     /// Enables / disables the internal pull up
+                    #[deprecated(note = "use `set_pull` instead")]
                     pub fn internal_pull_up(&mut self, on: bool) {
-                        let offset = 2 * $i;
-                        let value = if on { 0b01 } else { 0b00 };
-                        unsafe {
-                            &(*$GPIOX::ptr()).pupdr.modify(|r, w| {
-                                w.bits((r.bits() & !(0b11 << offset)) | (value << offset))
-                            })
-                        };
+                        set_pull($extigpionr, $i, if on { Pull::Up } else { Pull::None });
                     }
                 }
 
                 impl<MODE> $PXi<Alternate<MODE>> {
-                    /// Set pin speed
-                    pub fn set_speed(self, speed: Speed) -> Self {
-                        let offset = 2 * $i;
-
-                        unsafe {
-                            &(*$GPIOX::ptr()).ospeedr.modify(|r, w| {
-                                w.bits((r.bits() & !(0b11 << offset)) | ((speed as u32) << offset))
-                            })
-                        };
-
+                    /// Set the internal pull-up / pull-down
+                    pub fn set_pull(self, pull: Pull) -> Self {
+                        set_pull($extigpionr, $i, pull);
                         self
                     }
 
                     /// Enables / disables the internal pull up
+                    #[deprecated(note = "use `set_pull` instead")]
                     pub fn internal_pull_up(self, on: bool) -> Self {
-                        let offset = 2 * $i;
-                        let value = if on { 0b01 } else { 0b00 };
-                        unsafe {
-                            &(*$GPIOX::ptr()).pupdr.modify(|r, w| {
-                                w.bits((r.bits() & !(0b11 << offset)) | (value << offset))
-                            })
-                        };
-
-                        self
+                        self.set_pull(if on { Pull::Up } else { Pull::None })
                     }
-                }
 
-                impl<MODE> $PXi<Alternate<MODE>> {
                     /// Turns pin alternate configuration pin into open drain
                     pub fn set_open_drain(self) -> Self {
-                        let offset = $i;
-                        unsafe {
-                            &(*$GPIOX::ptr()).otyper.modify(|r, w| {
-                                w.bits(r.bits() | (1 << offset))
-                            })
-                        };
-
+                        set_open_drain($extigpionr, $i);
                         self
                     }
                 }
@@ -563,6 +1323,43 @@ macro_rules! gpio {
                             _mode: self._mode,
                         }
                     }
+
+                    /// Erases the pin number and the port from the type
+                    ///
+                    /// This is useful when you want to collect pins of different ports into an
+                    /// array
+                    pub fn erase(self) -> ErasedPin<MODE> {
+                        ErasedPin::new($extigpionr, $i)
+                    }
+
+                    /// Converts the pin into a `DynamicPin` whose mode can be changed at
+                    /// runtime, starting as a floating input
+                    pub fn into_dynamic(self) -> DynamicPin {
+                        DynamicPin::new($extigpionr, $i)
+                    }
+
+                    /// Locks the configuration of the pin until the next reset
+                    ///
+                    /// Only the first lock sequence of a port takes effect, add the pins to the
+                    /// port's `PortLock` to lock several pins of a port. Returns the pin back if
+                    /// the port is already locked.
+                    pub fn lock(self) -> Result<LockedPin<Self>, Self> {
+                        if lock_pins($extigpionr, 1 << $i) {
+                            Ok(LockedPin { pin: self })
+                        } else {
+                            Err(self)
+                        }
+                    }
+                }
+
+                impl<MODE> sealed::Sealed for $PXi<MODE> {}
+
+                impl<MODE> Lockable for $PXi<MODE> {
+                    type Port = $GPIOX;
+
+                    fn lock_mask(&self) -> u16 {
+                        1 << $i
+                    }
                 }
 
                 impl<MODE> OutputPin for $PXi<Output<MODE>> {
@@ -633,35 +1430,23 @@ macro_rules! gpio {
 
                     /// Generate interrupt on rising edge, falling edge or both
                     fn trigger_on_edge(&mut self, exti: &mut EXTI, edge: Edge) {
-                        match edge {
-                            Edge::RISING => {
-                                exti.rtsr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << $i)) });
-                                exti.ftsr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << $i)) });
-                            },
-                            Edge::FALLING => {
-                                exti.ftsr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << $i)) });
-                                exti.rtsr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << $i)) });
-                            },
-                            Edge::RISING_FALLING => {
-                                exti.rtsr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << $i)) });
-                                exti.ftsr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << $i)) });
-                            }
-                        }
+                        exti.trigger_on_edge(GpioLine::new($i), edge);
                     }
 
                     /// Enable external interrupts from this pin.
                     fn enable_interrupt(&mut self, exti: &mut EXTI) {
-                        exti.imr.modify(|r, w| unsafe { w.bits(r.bits() | (1 << $i)) });
+                        exti.listen(GpioLine::new($i));
                     }
 
                     /// Disable external interrupts from this pin
                     fn disable_interrupt(&mut self, exti: &mut EXTI) {
-                        exti.imr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << $i)) });
+                        exti.unlisten(GpioLine::new($i));
                     }
 
                     /// Clear the interrupt pending bit for this pin
                     fn clear_interrupt_pending_bit(&mut self) {
-                        unsafe { (*EXTI::ptr()).pr.write(|w| w.bits(1 << $i) ) };
+                        // NOTE(unsafe) atomic write to a write-1-to-clear register
+                        unsafe { (*EXTI::ptr()).clear_pending(GpioLine::new($i)) };
                     }
                 }
 
@@ -689,7 +1474,7 @@ macro_rules! gpio {
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOA, gpioa, gpioaen, PA, 0, [
+gpio!(GPIOA, gpioa, PA, 0, [
     PA0: (pa0, 0, Input<Floating>, exticr1),
     PA1: (pa1, 1, Input<Floating>, exticr1),
     PA2: (pa2, 2, Input<Floating>, exticr1),
@@ -727,7 +1512,7 @@ gpio!(GPIOA, gpioa, gpioaen, PA, 0, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOB, gpiob, gpioben, PB, 1, [
+gpio!(GPIOB, gpiob, PB, 1, [
     PB0: (pb0, 0, Input<Floating>, exticr1),
     PB1: (pb1, 1, Input<Floating>, exticr1),
     PB2: (pb2, 2, Input<Floating>, exticr1),
@@ -765,7 +1550,7 @@ gpio!(GPIOB, gpiob, gpioben, PB, 1, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOC, gpioc, gpiocen, PC, 2, [
+gpio!(GPIOC, gpioc, PC, 2, [
     PC0: (pc0, 0, Input<Floating>, exticr1),
     PC1: (pc1, 1, Input<Floating>, exticr1),
     PC2: (pc2, 2, Input<Floating>, exticr1),
@@ -802,7 +1587,7 @@ gpio!(GPIOC, gpioc, gpiocen, PC, 2, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOD, gpiod, gpioden, PD, 3, [
+gpio!(GPIOD, gpiod, PD, 3, [
     PD0: (pd0, 0, Input<Floating>, exticr1),
     PD1: (pd1, 1, Input<Floating>, exticr1),
     PD2: (pd2, 2, Input<Floating>, exticr1),
@@ -839,7 +1624,7 @@ gpio!(GPIOD, gpiod, gpioden, PD, 3, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOE, gpioe, gpioeen, PE, 4, [
+gpio!(GPIOE, gpioe, PE, 4, [
     PE0: (pe0, 0, Input<Floating>, exticr1),
     PE1: (pe1, 1, Input<Floating>, exticr1),
     PE2: (pe2, 2, Input<Floating>, exticr1),
@@ -874,7 +1659,7 @@ gpio!(GPIOE, gpioe, gpioeen, PE, 4, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOF, gpiof, gpiofen, PF, 5, [
+gpio!(GPIOF, gpiof, PF, 5, [
     PF0: (pf0, 0, Input<Floating>, exticr1),
     PF1: (pf1, 1, Input<Floating>, exticr1),
     PF2: (pf2, 2, Input<Floating>, exticr1),
@@ -909,7 +1694,7 @@ gpio!(GPIOF, gpiof, gpiofen, PF, 5, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOG, gpiog, gpiogen, PG, 6, [
+gpio!(GPIOG, gpiog, PG, 6, [
     PG0: (pg0, 0, Input<Floating>, exticr1),
     PG1: (pg1, 1, Input<Floating>, exticr1),
     PG2: (pg2, 2, Input<Floating>, exticr1),
@@ -946,7 +1731,7 @@ gpio!(GPIOG, gpiog, gpiogen, PG, 6, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOH, gpioh, gpiohen, PH, 7, [
+gpio!(GPIOH, gpioh, PH, 7, [
     PH0: (ph0, 0, Input<Floating>, exticr1),
     PH1: (ph1, 1, Input<Floating>, exticr1),
     PH2: (ph2, 2, Input<Floating>, exticr1),
@@ -966,7 +1751,7 @@ gpio!(GPIOH, gpioh, gpiohen, PH, 7, [
 ]);
 
 #[cfg(any(feature = "stm32f401"))]
-gpio!(GPIOH, gpioh, gpiohen, PH, 7, [
+gpio!(GPIOH, gpioh, PH, 7, [
     PH0: (ph0, 0, Input<Floating>, exticr1),
     PH1: (ph1, 1, Input<Floating>, exticr1),
 ]);
@@ -983,7 +1768,7 @@ gpio!(GPIOH, gpioh, gpiohen, PH, 7, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOI, gpioi, gpioien, PI, 8, [
+gpio!(GPIOI, gpioi, PI, 8, [
     PI0: (pi0, 0, Input<Floating>, exticr1),
     PI1: (pi1, 1, Input<Floating>, exticr1),
     PI2: (pi2, 2, Input<Floating>, exticr1),
@@ -1010,7 +1795,7 @@ gpio!(GPIOI, gpioi, gpioien, PI, 8, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOJ, gpioj, gpiojen, PJ, 9, [
+gpio!(GPIOJ, gpioj, PJ, 9, [
     PJ0: (pj0, 0, Input<Floating>, exticr1),
     PJ1: (pj1, 1, Input<Floating>, exticr1),
     PJ2: (pj2, 2, Input<Floating>, exticr1),
@@ -1037,7 +1822,7 @@ gpio!(GPIOJ, gpioj, gpiojen, PJ, 9, [
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-gpio!(GPIOK, gpiok, gpioken, PK, 10, [
+gpio!(GPIOK, gpiok, PK, 10, [
     PK0: (pk0, 0, Input<Floating>, exticr1),
     PK1: (pk1, 1, Input<Floating>, exticr1),
     PK2: (pk2, 2, Input<Floating>, exticr1),
@@ -21,7 +21,7 @@ use crate::stm32::i2c1;
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-use crate::stm32::{I2C1, I2C2, RCC};
+use crate::stm32::{I2C1, I2C2};
 #[cfg(any(
     feature = "stm32f401",
     feature = "stm32f405",
@@ -190,7 +190,7 @@ use crate::gpio::{Alternate, AF4};
 ))]
 use crate::gpio::AF9;
 
-use crate::rcc::Clocks;
+use crate::rcc::{BusClock, Clocks, Enable, Reset};
 use crate::time::{Hertz, KiloHertz, U32Ext};
 
 /// I2C abstraction
@@ -529,18 +529,14 @@ impl<PINS> I2c<I2C1, PINS> {
     where
         PINS: Pins<I2C1>,
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
-
         // Enable clock for I2C1
-        rcc.apb1enr.modify(|_, w| w.i2c1en().set_bit());
+        I2C1::enable();
 
         // Reset I2C1
-        rcc.apb1rstr.modify(|_, w| w.i2c1rst().set_bit());
-        rcc.apb1rstr.modify(|_, w| w.i2c1rst().clear_bit());
+        I2C1::reset();
 
         let i2c = I2c { i2c, pins };
-        i2c.i2c_init(speed, clocks.pclk1());
+        i2c.i2c_init(speed, I2C1::clock(&clocks));
         i2c
     }
 }
@@ -569,18 +565,14 @@ impl<PINS> I2c<I2C2, PINS> {
     where
         PINS: Pins<I2C2>,
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
-
         // Enable clock for I2C2
-        rcc.apb1enr.modify(|_, w| w.i2c2en().set_bit());
+        I2C2::enable();
 
         // Reset I2C2
-        rcc.apb1rstr.modify(|_, w| w.i2c2rst().set_bit());
-        rcc.apb1rstr.modify(|_, w| w.i2c2rst().clear_bit());
+        I2C2::reset();
 
         let i2c = I2c { i2c, pins };
-        i2c.i2c_init(speed, clocks.pclk1());
+        i2c.i2c_init(speed, I2C2::clock(&clocks));
         i2c
     }
 }
@@ -608,18 +600,14 @@ impl<PINS> I2c<I2C3, PINS> {
     where
         PINS: Pins<I2C3>,
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
-
         // Enable clock for I2C3
-        rcc.apb1enr.modify(|_, w| w.i2c3en().set_bit());
+        I2C3::enable();
 
         // Reset I2C3
-        rcc.apb1rstr.modify(|_, w| w.i2c3rst().set_bit());
-        rcc.apb1rstr.modify(|_, w| w.i2c3rst().clear_bit());
+        I2C3::reset();
 
         let i2c = I2c { i2c, pins };
-        i2c.i2c_init(speed, clocks.pclk1());
+        i2c.i2c_init(speed, I2C3::clock(&clocks));
         i2c
     }
 }
@@ -628,6 +616,14 @@ impl<I2C, PINS> I2c<I2C, PINS>
 where
     I2C: Deref<Target = i2c1::RegisterBlock>,
 {
+    /// Reconfigures the bus speed after the clocks were reconfigured with `CFGR::freeze`
+    pub fn reconfigure(&mut self, speed: KiloHertz, clocks: Clocks)
+    where
+        I2C: BusClock,
+    {
+        self.i2c_init(speed, I2C::clock(&clocks));
+    }
+
     fn i2c_init(&self, speed: KiloHertz, pclk: Hertz) {
         let speed: Hertz = speed.into();
 
@@ -4,6 +4,7 @@ pub use embedded_hal::digital::v2::StatefulOutputPin as _embedded_hal_digital_v2
 pub use embedded_hal::digital::v2::ToggleableOutputPin as _embedded_hal_digital_v2_ToggleableOutputPin;
 pub use embedded_hal::prelude::*;
 
+pub use crate::exti::ExtiExt as _stm32f4xx_hal_exti_ExtiExt;
 pub use crate::gpio::GpioExt as _stm32f4xx_hal_gpio_GpioExt;
 pub use crate::i2c::Pins as _stm32f4xx_hal_i2c_Pins;
 pub use crate::rcc::RccExt as _stm32f4xx_hal_rcc_RccExt;
@@ -0,0 +1,2731 @@
+//! Pulse width modulation
+//!
+//! Every timer channel that has a pin can be used as a PWM output. The timer is configured
+//! with [`Pwm`], which implements `embedded_hal::Pwm`, and can then be split into one
+//! `PwmChannel` per pin, each implementing `embedded_hal::PwmPin`.
+//!
+//! ```
+//! let gpioa = dp.GPIOA.split();
+//! let pins = (
+//!     gpioa.pa8.into_alternate_af1(),
+//!     gpioa.pa9.into_alternate_af1(),
+//! );
+//!
+//! let pwm = Pwm::tim1(dp.TIM1, pins, 20.khz(), clocks);
+//! let (mut ch1, mut ch2) = pwm.split();
+//! let max_duty = ch1.get_max_duty();
+//! ch1.set_duty(max_duty / 2);
+//! ch1.enable();
+//! ```
+
+use core::marker::PhantomData;
+
+use cortex_m::interrupt;
+
+use cast::u32;
+
+use crate::hal;
+use crate::rcc::{BusTimerClock, Clocks, Enable, Reset};
+use crate::time::Hertz;
+
+use crate::gpio::gpioa::*;
+use crate::gpio::gpiob::*;
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::gpio::gpioc::*;
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::gpio::gpiod::*;
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::gpio::gpioe::*;
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::gpio::gpiof::*;
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::gpio::gpioh::*;
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::gpio::gpioi::*;
+use crate::gpio::{Alternate, AF1, AF2, AF3};
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::gpio::AF9;
+
+use crate::stm32::{TIM1, TIM11, TIM5, TIM9};
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::stm32::{TIM10, TIM2, TIM3, TIM4};
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+use crate::stm32::{TIM12, TIM13, TIM14, TIM8};
+
+/// Channel 1 (type state)
+pub struct C1;
+/// Channel 2 (type state)
+pub struct C2;
+/// Channel 3 (type state)
+pub struct C3;
+/// Channel 4 (type state)
+pub struct C4;
+
+/// Timer channel, used by the `embedded_hal::Pwm` implementation of [`Pwm`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Channel {
+    C1 = 0,
+    C2 = 1,
+    C3 = 2,
+    C4 = 3,
+}
+
+/// Output polarity of a channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Polarity {
+    /// The output is high while the counter is below the duty cycle
+    ActiveHigh,
+    /// The output is low while the counter is below the duty cycle
+    ActiveLow,
+}
+
+/// Counter alignment
+///
+/// In the center aligned modes the counter counts up and down, halving the PWM frequency for
+/// the same period. The three center aligned modes only differ in when the compare interrupt
+/// flags are set, the frequency set by `set_period` is kept in all of them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Alignment {
+    Edge = 0b00,
+    Center1 = 0b01,
+    Center2 = 0b10,
+    Center3 = 0b11,
+}
+
+pub trait PinC1<TIM> {}
+pub trait PinC2<TIM> {}
+pub trait PinC3<TIM> {}
+pub trait PinC4<TIM> {}
+
+/// A pin or a tuple of pins that can be driven by the channels `CHANNELS` of `TIM`
+pub trait Pins<TIM, CHANNELS> {
+    const C1: bool = false;
+    const C2: bool = false;
+    const C3: bool = false;
+    const C4: bool = false;
+    type Channels;
+
+    #[doc(hidden)]
+    fn channels() -> Self::Channels;
+}
+
+macro_rules! pins_impl {
+    ( $( ( $($PINX:ident),+ ), ( $($TRAIT:ident),+ ), ( $($ENCHX:ident),+ ); )+ ) => {
+        $(
+            #[allow(unused_parens)]
+            impl<TIM, $($PINX,)+> Pins<TIM, ($($ENCHX),+)> for ($($PINX),+)
+            where
+                $($PINX: $TRAIT<TIM>,)+
+            {
+                $(const $ENCHX: bool = true;)+
+                type Channels = ($(PwmChannel<TIM, $ENCHX>),+);
+
+                fn channels() -> Self::Channels {
+                    ($(PwmChannel::<TIM, $ENCHX>::new()),+)
+                }
+            }
+        )+
+    };
+}
+
+pins_impl!(
+    (P1, P2, P3, P4), (PinC1, PinC2, PinC3, PinC4), (C1, C2, C3, C4);
+    (P2, P3, P4), (PinC2, PinC3, PinC4), (C2, C3, C4);
+    (P1, P3, P4), (PinC1, PinC3, PinC4), (C1, C3, C4);
+    (P1, P2, P4), (PinC1, PinC2, PinC4), (C1, C2, C4);
+    (P1, P2, P3), (PinC1, PinC2, PinC3), (C1, C2, C3);
+    (P3, P4), (PinC3, PinC4), (C3, C4);
+    (P2, P4), (PinC2, PinC4), (C2, C4);
+    (P2, P3), (PinC2, PinC3), (C2, C3);
+    (P1, P4), (PinC1, PinC4), (C1, C4);
+    (P1, P3), (PinC1, PinC3), (C1, C3);
+    (P1, P2), (PinC1, PinC2), (C1, C2);
+    (P1), (PinC1), (C1);
+    (P2), (PinC2), (C2);
+    (P3), (PinC3), (C3);
+    (P4), (PinC4), (C4);
+);
+
+/// PWM generator using all channels of a timer that have a pin
+pub struct Pwm<TIM, PINS> {
+    tim: TIM,
+    pins: PINS,
+    clk: Hertz,
+    center_aligned: bool,
+}
+
+/// A single PWM output, obtained by splitting a [`Pwm`]
+pub struct PwmChannel<TIM, CHANNEL> {
+    _tim: PhantomData<TIM>,
+    _channel: PhantomData<CHANNEL>,
+}
+
+impl<TIM, CHANNEL> PwmChannel<TIM, CHANNEL> {
+    fn new() -> Self {
+        PwmChannel {
+            _tim: PhantomData,
+            _channel: PhantomData,
+        }
+    }
+}
+
+macro_rules! hal {
+    ($($TIM:ident: ($tim:ident, $bits:ident, [
+        $($C:ident: ($index:expr, $ccmr:ident, $ocpe:ident, $ocm:ident, $ccr:ident),)+
+    ] $(, $bdtr:ident)?),)+) => {
+        $(
+            impl<PINS> Pwm<$TIM, PINS> {
+                /// Configures a timer as PWM generator with the given frequency
+                ///
+                /// Channels with a pin are set up in PWM mode 1 and enabled with a duty cycle of 0.
+                pub fn $tim<CHANNELS, T>(tim: $TIM, pins: PINS, freq: T, clocks: Clocks) -> Self
+                where
+                    PINS: Pins<$TIM, CHANNELS>,
+                    T: Into<Hertz>,
+                {
+                    // enable and reset peripheral to a clean slate state
+                    $TIM::enable();
+                    $TIM::reset();
+
+                    $(
+                        if PINS::$C {
+                            // PWM mode 1 with preloaded compare register
+                            #[allow(unused_unsafe)]
+                            tim.$ccmr().modify(|_, w| unsafe { w.$ocpe().set_bit().$ocm().bits(0b110) });
+                            tim.ccer.modify(|r, w| unsafe { w.bits(r.bits() | (1 << (4 * $index))) });
+                        }
+                    )+
+
+                    let mut pwm = Pwm {
+                        tim,
+                        pins,
+                        clk: $TIM::timer_clock(&clocks),
+                        center_aligned: false,
+                    };
+                    hal::Pwm::set_period(&mut pwm, freq);
+
+                    $(
+                        // advanced timers only drive their outputs with the main output enabled
+                        pwm.tim.$bdtr.modify(|_, w| w.moe().set_bit());
+                    )?
+
+                    pwm.tim.cr1.modify(|_, w| w.arpe().set_bit().cen().set_bit());
+
+                    pwm
+                }
+
+                /// Updates the timer clock after the clocks were reconfigured with `CFGR::freeze`,
+                /// keeping the period and the duty cycles
+                pub fn reconfigure(&mut self, clocks: Clocks) {
+                    let period = hal::Pwm::get_period(self);
+                    let old_arr = u64::from(self.tim.arr.read().bits()) + 1;
+
+                    self.clk = $TIM::timer_clock(&clocks);
+                    hal::Pwm::set_period(self, period);
+
+                    let new_arr = u64::from(self.tim.arr.read().bits()) + 1;
+                    $(
+                        let duty = u64::from(self.tim.$ccr.read().bits());
+                        self.tim.$ccr.write(|w| unsafe { w.bits((duty * new_arr / old_arr) as u32) });
+                    )+
+                }
+
+                /// Splits the generator into one `PwmChannel` per pin
+                pub fn split<CHANNELS>(self) -> PINS::Channels
+                where
+                    PINS: Pins<$TIM, CHANNELS>,
+                {
+                    PINS::channels()
+                }
+
+                /// Sets the output polarity of a channel
+                pub fn set_polarity(&mut self, channel: Channel, polarity: Polarity) {
+                    let bit = 1 << (4 * channel as u32 + 1);
+                    self.tim.ccer.modify(|r, w| unsafe {
+                        match polarity {
+                            Polarity::ActiveHigh => w.bits(r.bits() & !bit),
+                            Polarity::ActiveLow => w.bits(r.bits() | bit),
+                        }
+                    });
+                }
+
+                /// Stops the timer and releases the timer and the pins
+                pub fn release(self) -> ($TIM, PINS) {
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    (self.tim, self.pins)
+                }
+            }
+
+            impl<PINS> hal::Pwm for Pwm<$TIM, PINS> {
+                type Channel = Channel;
+                type Time = Hertz;
+                type Duty = $bits;
+
+                fn disable(&mut self, channel: Channel) {
+                    self.tim.ccer.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << (4 * channel as u32))) });
+                }
+
+                fn enable(&mut self, channel: Channel) {
+                    self.tim.ccer.modify(|r, w| unsafe { w.bits(r.bits() | (1 << (4 * channel as u32))) });
+                }
+
+                fn get_period(&self) -> Hertz {
+                    let psc = u64::from(self.tim.psc.read().psc().bits());
+                    let arr = u64::from(self.tim.arr.read().bits());
+                    let div = if self.center_aligned { 2 } else { 1 };
+
+                    Hertz((u64::from(self.clk.0) / ((psc + 1) * (arr + 1) * div)) as u32)
+                }
+
+                fn get_duty(&self, channel: Channel) -> $bits {
+                    match channel {
+                        $(Channel::$C => self.tim.$ccr.read().bits() as $bits,)+
+                        #[allow(unreachable_patterns)]
+                        _ => 0,
+                    }
+                }
+
+                /// Returns the value of the auto-reload register, a duty cycle of this value keeps
+                /// the output active for all but the last count of each period
+                fn get_max_duty(&self) -> $bits {
+                    self.tim.arr.read().bits() as $bits
+                }
+
+                fn set_duty(&mut self, channel: Channel, duty: $bits) {
+                    match channel {
+                        $(Channel::$C => self.tim.$ccr.write(|w| unsafe { w.bits(duty.into()) }),)+
+                        #[allow(unreachable_patterns)]
+                        _ => {}
+                    }
+                }
+
+                /// Sets the PWM frequency
+                ///
+                /// Frequencies the timer can't generate are clamped to the closest one it can.
+                fn set_period<T>(&mut self, period: T)
+                where
+                    T: Into<Hertz>,
+                {
+                    let div = if self.center_aligned { 2 } else { 1 };
+                    let max_arr = $bits::max_value().into();
+                    let (psc, arr) = period_bits(self.clk, period.into(), div, max_arr);
+
+                    self.tim.psc.write(|w| w.psc().bits(psc));
+                    self.tim.arr.write(|w| unsafe { w.bits(arr) });
+                    // load the new prescaler and auto-reload values
+                    self.tim.egr.write(|w| w.ug().set_bit());
+                }
+            }
+
+            $(
+                impl hal::PwmPin for PwmChannel<$TIM, $C> {
+                    type Duty = $bits;
+
+                    fn disable(&mut self) {
+                        // NOTE(unsafe) CCER is shared between the channels, so it is only
+                        // modified in a critical section
+                        interrupt::free(|_| unsafe {
+                            (*$TIM::ptr()).ccer.modify(|r, w| w.bits(r.bits() & !(1 << (4 * $index))))
+                        });
+                    }
+
+                    fn enable(&mut self) {
+                        // NOTE(unsafe) CCER is shared between the channels, so it is only
+                        // modified in a critical section
+                        interrupt::free(|_| unsafe {
+                            (*$TIM::ptr()).ccer.modify(|r, w| w.bits(r.bits() | (1 << (4 * $index))))
+                        });
+                    }
+
+                    fn get_duty(&self) -> $bits {
+                        // NOTE(unsafe) atomic read with no side effects
+                        unsafe { (*$TIM::ptr()).$ccr.read().bits() as $bits }
+                    }
+
+                    fn get_max_duty(&self) -> $bits {
+                        // NOTE(unsafe) atomic read with no side effects
+                        unsafe { (*$TIM::ptr()).arr.read().bits() as $bits }
+                    }
+
+                    fn set_duty(&mut self, duty: $bits) {
+                        // NOTE(unsafe) this channel owns its compare register
+                        unsafe { (*$TIM::ptr()).$ccr.write(|w| w.bits(duty.into())) }
+                    }
+                }
+
+                impl PwmChannel<$TIM, $C> {
+                    /// Sets the output polarity of this channel
+                    pub fn set_polarity(&mut self, polarity: Polarity) {
+                        let bit = 1 << (4 * $index + 1);
+                        // NOTE(unsafe) CCER is shared between the channels, so it is only
+                        // modified in a critical section
+                        interrupt::free(|_| unsafe {
+                            (*$TIM::ptr()).ccer.modify(|r, w| match polarity {
+                                Polarity::ActiveHigh => w.bits(r.bits() & !bit),
+                                Polarity::ActiveLow => w.bits(r.bits() | bit),
+                            })
+                        });
+                    }
+                }
+            )+
+        )+
+    }
+}
+
+macro_rules! alignment {
+    ($($TIM:ident,)+) => {
+        $(
+            impl<PINS> Pwm<$TIM, PINS> {
+                /// Sets the counter alignment, keeping the configured PWM frequency
+                pub fn set_alignment(&mut self, alignment: Alignment) {
+                    let period = hal::Pwm::get_period(self);
+
+                    // The alignment can only be changed while the counter is stopped
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    #[allow(unused_unsafe)]
+                    self.tim.cr1.modify(|_, w| unsafe { w.cms().bits(alignment as u8) });
+                    self.center_aligned = alignment != Alignment::Edge;
+
+                    hal::Pwm::set_period(self, period);
+                    self.tim.cr1.modify(|_, w| w.cen().set_bit());
+                }
+            }
+        )+
+    }
+}
+
+hal! {
+    TIM1: (tim1, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+        C2: (1, ccmr1_output, oc2pe, oc2m, ccr2),
+        C3: (2, ccmr2_output, oc3pe, oc3m, ccr3),
+        C4: (3, ccmr2_output, oc4pe, oc4m, ccr4),
+    ], bdtr),
+    TIM5: (tim5, u32, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+        C2: (1, ccmr1_output, oc2pe, oc2m, ccr2),
+        C3: (2, ccmr2_output, oc3pe, oc3m, ccr3),
+        C4: (3, ccmr2_output, oc4pe, oc4m, ccr4),
+    ]),
+    TIM9: (tim9, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+        C2: (1, ccmr1_output, oc2pe, oc2m, ccr2),
+    ]),
+    TIM11: (tim11, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+    ]),
+}
+
+alignment! {
+    TIM1,
+    TIM5,
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+hal! {
+    TIM2: (tim2, u32, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+        C2: (1, ccmr1_output, oc2pe, oc2m, ccr2),
+        C3: (2, ccmr2_output, oc3pe, oc3m, ccr3),
+        C4: (3, ccmr2_output, oc4pe, oc4m, ccr4),
+    ]),
+    TIM3: (tim3, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+        C2: (1, ccmr1_output, oc2pe, oc2m, ccr2),
+        C3: (2, ccmr2_output, oc3pe, oc3m, ccr3),
+        C4: (3, ccmr2_output, oc4pe, oc4m, ccr4),
+    ]),
+    TIM4: (tim4, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+        C2: (1, ccmr1_output, oc2pe, oc2m, ccr2),
+        C3: (2, ccmr2_output, oc3pe, oc3m, ccr3),
+        C4: (3, ccmr2_output, oc4pe, oc4m, ccr4),
+    ]),
+    TIM10: (tim10, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+    ]),
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+alignment! {
+    TIM2,
+    TIM3,
+    TIM4,
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+hal! {
+    TIM8: (tim8, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+        C2: (1, ccmr1_output, oc2pe, oc2m, ccr2),
+        C3: (2, ccmr2_output, oc3pe, oc3m, ccr3),
+        C4: (3, ccmr2_output, oc4pe, oc4m, ccr4),
+    ], bdtr),
+    TIM12: (tim12, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+        C2: (1, ccmr1_output, oc2pe, oc2m, ccr2),
+    ]),
+    TIM13: (tim13, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+    ]),
+    TIM14: (tim14, u16, [
+        C1: (0, ccmr1_output, oc1pe, oc1m, ccr1),
+    ]),
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+alignment! {
+    TIM8,
+}
+
+/// PWM error
+#[derive(Debug)]
+pub enum Error {
+    /// The requested dead time is longer than the timer can insert
+    DeadTime,
+    /// The break input is still active, the outputs stay disabled
+    BreakActive,
+    #[doc(hidden)]
+    _Extensible,
+}
+
+pub trait PinC1N<TIM> {}
+pub trait PinC2N<TIM> {}
+pub trait PinC3N<TIM> {}
+pub trait PinBkin<TIM> {}
+
+/// Filler type for a [`ComplementaryPwm`] without break input
+pub struct NoBkin;
+
+/// Active level of the break input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BreakPolarity {
+    ActiveLow,
+    ActiveHigh,
+}
+
+/// A complementary output pin or a tuple of them for the channels `CHANNELS` of `TIM`
+pub trait ComplementaryPins<TIM, CHANNELS> {
+    const C1N: bool = false;
+    const C2N: bool = false;
+    const C3N: bool = false;
+}
+
+macro_rules! complementary_pins_impl {
+    ( $( ( $($PINX:ident),+ ), ( $($TRAIT:ident),+ ), ( $($CHX:ident),+ ), ( $($ENCHX:ident),+ ); )+ ) => {
+        $(
+            #[allow(unused_parens)]
+            impl<TIM, $($PINX,)+> ComplementaryPins<TIM, ($($CHX),+)> for ($($PINX),+)
+            where
+                $($PINX: $TRAIT<TIM>,)+
+            {
+                $(const $ENCHX: bool = true;)+
+            }
+        )+
+    };
+}
+
+complementary_pins_impl!(
+    (P1, P2, P3), (PinC1N, PinC2N, PinC3N), (C1, C2, C3), (C1N, C2N, C3N);
+    (P2, P3), (PinC2N, PinC3N), (C2, C3), (C2N, C3N);
+    (P1, P3), (PinC1N, PinC3N), (C1, C3), (C1N, C3N);
+    (P1, P2), (PinC1N, PinC2N), (C1, C2), (C1N, C2N);
+    (P1), (PinC1N), (C1), (C1N);
+    (P2), (PinC2N), (C2), (C2N);
+    (P3), (PinC3N), (C3), (C3N);
+);
+
+/// PWM generator of an advanced timer with complementary outputs, dead-time insertion and an
+/// optional break input
+///
+/// ```
+/// let pwm = Pwm::tim1(dp.TIM1, (pa8, pa9, pa10), 20.khz(), clocks);
+/// let mut pwm = pwm.complementary((pb13, pb14, pb15)).with_break(pb12, BreakPolarity::ActiveLow);
+/// pwm.set_dead_time(500).unwrap();
+/// ```
+pub struct ComplementaryPwm<TIM, PINS, NPINS, BKIN> {
+    pwm: Pwm<TIM, PINS>,
+    npins: NPINS,
+    bkin: BKIN,
+    // CCxNE bits of the channels with a complementary pin
+    nchannels: u32,
+}
+
+/// Computes PSC and ARR for a PWM frequency of `freq`
+///
+/// Frequencies above the timer clock give the shortest period, 0 Hz and frequencies below the
+/// slowest period the longest one.
+fn period_bits(clk: Hertz, freq: Hertz, div: u32, max_arr: u32) -> (u16, u32) {
+    let ticks = match freq.0 {
+        0 => u64::max_value(),
+        freq => u64::from(clk.0 / freq / div).max(1),
+    };
+    let psc = ((ticks - 1) / (u64::from(max_arr) + 1)).min(u64::from(u16::max_value()));
+    let arr = (ticks / (psc + 1)).max(1) - 1;
+
+    (psc as u16, arr.min(u64::from(max_arr)) as u32)
+}
+
+/// Computes CR1.CKD and BDTR.DTG for a dead time of `ticks` timer clock cycles
+fn dead_time_bits(ticks: u32) -> Option<(u8, u8)> {
+    for ckd in 0..3 {
+        // dead time in units of the dead-time clock, rounded up
+        let t = (ticks + (1 << ckd) - 1) >> ckd;
+        let dtg = match t {
+            0..=127 => t,
+            128..=254 => 0b1000_0000 | ((t + 1) / 2 - 64),
+            255..=504 => 0b1100_0000 | ((t + 7) / 8 - 32),
+            505..=1008 => 0b1110_0000 | ((t + 15) / 16 - 32),
+            _ => continue,
+        };
+        return Some((ckd, dtg as u8));
+    }
+    None
+}
+
+macro_rules! complementary {
+    ($($TIM:ident,)+) => {
+        $(
+            impl<PINS> Pwm<$TIM, PINS> {
+                /// Adds complementary outputs to the channels with a pin in `npins`
+                ///
+                /// The complementary outputs follow the enable state of their channel. No dead
+                /// time is inserted until it is set with `set_dead_time`.
+                pub fn complementary<NCHANNELS, NPINS>(
+                    self,
+                    npins: NPINS,
+                ) -> ComplementaryPwm<$TIM, PINS, NPINS, NoBkin>
+                where
+                    NPINS: ComplementaryPins<$TIM, NCHANNELS>,
+                {
+                    let nchannels = if NPINS::C1N { 1 << 2 } else { 0 }
+                        | if NPINS::C2N { 1 << 6 } else { 0 }
+                        | if NPINS::C3N { 1 << 10 } else { 0 };
+
+                    self.tim.ccer.modify(|r, w| unsafe {
+                        w.bits(r.bits() | ((r.bits() & 0x111) << 2) & nchannels)
+                    });
+
+                    ComplementaryPwm {
+                        pwm: self,
+                        npins,
+                        bkin: NoBkin,
+                        nchannels,
+                    }
+                }
+            }
+
+            impl<PINS, NPINS> ComplementaryPwm<$TIM, PINS, NPINS, NoBkin> {
+                /// Enables the break input
+                ///
+                /// An active break input clears the main output enable in hardware, which puts all
+                /// outputs into their idle state without any involvement of the CPU.
+                pub fn with_break<BKIN>(
+                    self,
+                    bkin: BKIN,
+                    polarity: BreakPolarity,
+                ) -> ComplementaryPwm<$TIM, PINS, NPINS, BKIN>
+                where
+                    BKIN: PinBkin<$TIM>,
+                {
+                    self.pwm.tim.bdtr.modify(|_, w| {
+                        w.bkp()
+                            .bit(polarity == BreakPolarity::ActiveHigh)
+                            .bke()
+                            .set_bit()
+                    });
+
+                    ComplementaryPwm {
+                        pwm: self.pwm,
+                        npins: self.npins,
+                        bkin,
+                        nchannels: self.nchannels,
+                    }
+                }
+            }
+
+            impl<PINS, NPINS, BKIN> ComplementaryPwm<$TIM, PINS, NPINS, BKIN> {
+                /// Sets the dead time between an output and its complementary output, rounded up
+                /// to the resolution of the dead-time generator
+                pub fn set_dead_time(&mut self, nanoseconds: u32) -> Result<(), Error> {
+                    let ticks = (u64::from(nanoseconds) * u64::from(self.pwm.clk.0)
+                        + 999_999_999)
+                        / 1_000_000_000;
+                    let (ckd, dtg) = u32(ticks)
+                        .ok()
+                        .and_then(dead_time_bits)
+                        .ok_or(Error::DeadTime)?;
+
+                    #[allow(unused_unsafe)]
+                    self.pwm.tim.cr1.modify(|_, w| unsafe { w.ckd().bits(ckd) });
+                    #[allow(unused_unsafe)]
+                    self.pwm.tim.bdtr.modify(|_, w| unsafe { w.dtg().bits(dtg) });
+
+                    Ok(())
+                }
+
+                /// Sets the output polarity of the complementary output of a channel
+                ///
+                /// Channel 4 has no complementary output and is ignored.
+                pub fn set_complementary_polarity(&mut self, channel: Channel, polarity: Polarity) {
+                    if channel == Channel::C4 {
+                        return;
+                    }
+
+                    let bit = 1 << (4 * channel as u32 + 3);
+                    self.pwm.tim.ccer.modify(|r, w| unsafe {
+                        match polarity {
+                            Polarity::ActiveHigh => w.bits(r.bits() & !bit),
+                            Polarity::ActiveLow => w.bits(r.bits() | bit),
+                        }
+                    });
+                }
+
+                /// Sets the output polarity of a channel
+                pub fn set_polarity(&mut self, channel: Channel, polarity: Polarity) {
+                    self.pwm.set_polarity(channel, polarity);
+                }
+
+                /// Re-enables the outputs automatically at the next update event once the break
+                /// input is inactive, instead of requiring a call to `recover`
+                pub fn set_automatic_output_enable(&mut self, enable: bool) {
+                    self.pwm.tim.bdtr.modify(|_, w| w.aoe().bit(enable));
+                }
+
+                /// Returns `true` if the outputs are enabled, i.e. no break has occurred since
+                /// the last `recover`
+                pub fn is_output_enabled(&self) -> bool {
+                    self.pwm.tim.bdtr.read().moe().bit_is_set()
+                }
+
+                /// Disables all outputs, as if the break input had become active
+                pub fn trip(&mut self) {
+                    self.pwm.tim.egr.write(|w| w.bg().set_bit());
+                }
+
+                /// Clears the break flag and enables the outputs again
+                ///
+                /// Fails if the break input is still active.
+                pub fn recover(&mut self) -> Result<(), Error> {
+                    self.clear_break();
+                    self.pwm.tim.bdtr.modify(|_, w| w.moe().set_bit());
+
+                    if self.is_output_enabled() {
+                        Ok(())
+                    } else {
+                        Err(Error::BreakActive)
+                    }
+                }
+
+                /// Starts listening for break events
+                pub fn listen_break(&mut self) {
+                    self.pwm.tim.dier.modify(|_, w| w.bie().set_bit());
+                }
+
+                /// Stops listening for break events
+                pub fn unlisten_break(&mut self) {
+                    self.pwm.tim.dier.modify(|_, w| w.bie().clear_bit());
+                }
+
+                /// Returns `true` if a break has occurred
+                pub fn is_break(&self) -> bool {
+                    self.pwm.tim.sr.read().bif().bit_is_set()
+                }
+
+                /// Clears the break flag
+                pub fn clear_break(&mut self) {
+                    self.pwm.tim.sr.modify(|_, w| w.bif().clear_bit());
+                }
+
+                /// Disables the complementary outputs and the break input and releases them
+                pub fn release(self) -> (Pwm<$TIM, PINS>, NPINS, BKIN) {
+                    let nchannels = self.nchannels;
+                    self.pwm.tim.ccer.modify(|r, w| unsafe { w.bits(r.bits() & !nchannels) });
+                    self.pwm.tim.bdtr.modify(|_, w| w.bke().clear_bit());
+
+                    (self.pwm, self.npins, self.bkin)
+                }
+            }
+
+            impl<PINS, NPINS, BKIN> hal::Pwm for ComplementaryPwm<$TIM, PINS, NPINS, BKIN> {
+                type Channel = Channel;
+                type Time = Hertz;
+                type Duty = u16;
+
+                fn disable(&mut self, channel: Channel) {
+                    let mask = 0b101 << (4 * channel as u32);
+                    self.pwm.tim.ccer.modify(|r, w| unsafe { w.bits(r.bits() & !mask) });
+                }
+
+                fn enable(&mut self, channel: Channel) {
+                    let mask = (1 << (4 * channel as u32))
+                        | (self.nchannels & (1 << (4 * channel as u32 + 2)));
+                    self.pwm.tim.ccer.modify(|r, w| unsafe { w.bits(r.bits() | mask) });
+                }
+
+                fn get_period(&self) -> Hertz {
+                    hal::Pwm::get_period(&self.pwm)
+                }
+
+                fn get_duty(&self, channel: Channel) -> u16 {
+                    hal::Pwm::get_duty(&self.pwm, channel)
+                }
+
+                fn get_max_duty(&self) -> u16 {
+                    hal::Pwm::get_max_duty(&self.pwm)
+                }
+
+                fn set_duty(&mut self, channel: Channel, duty: u16) {
+                    hal::Pwm::set_duty(&mut self.pwm, channel, duty)
+                }
+
+                fn set_period<T>(&mut self, period: T)
+                where
+                    T: Into<Hertz>,
+                {
+                    hal::Pwm::set_period(&mut self.pwm, period)
+                }
+            }
+        )+
+    }
+}
+
+complementary! {
+    TIM1,
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+complementary! {
+    TIM8,
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM1> for PA8<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM1> for PA9<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM1> for PA10<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM1> for PA11<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM1> for PE9<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM1> for PE11<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM1> for PE13<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM1> for PE14<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM2> for PA0<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM2> for PA5<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM2> for PA15<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM2> for PA1<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM2> for PB3<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM2> for PA2<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM2> for PB10<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM2> for PA3<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM2> for PB11<Alternate<AF1>> {}
+
+#[cfg(feature = "stm32f446")]
+impl PinC1<TIM2> for PB8<Alternate<AF1>> {}
+
+#[cfg(feature = "stm32f446")]
+impl PinC2<TIM2> for PB9<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM3> for PA6<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM3> for PB4<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM3> for PC6<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM3> for PA7<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM3> for PB5<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM3> for PC7<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM3> for PB0<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM3> for PC8<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM3> for PB1<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM3> for PC9<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM4> for PB6<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM4> for PD12<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM4> for PB7<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM4> for PD13<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM4> for PB8<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM4> for PD14<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM4> for PB9<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM4> for PD15<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM5> for PA0<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM5> for PA1<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM5> for PA2<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM5> for PA3<Alternate<AF2>> {}
+
+#[cfg(feature = "stm32f410")]
+impl PinC1<TIM5> for PB12<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM5> for PH10<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM5> for PH11<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM5> for PH12<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM5> for PI0<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+impl PinC1<TIM5> for PF3<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+impl PinC2<TIM5> for PF4<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+impl PinC3<TIM5> for PF5<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+impl PinC4<TIM5> for PF10<Alternate<AF2>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM8> for PC6<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM8> for PC7<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM8> for PC8<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM8> for PC9<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM8> for PI5<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM8> for PI6<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3<TIM8> for PI7<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC4<TIM8> for PI2<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM9> for PA2<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM9> for PA3<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM9> for PE5<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM9> for PE6<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM10> for PB8<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM10> for PF6<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM11> for PB9<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM11> for PF7<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM12> for PB14<Alternate<AF9>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM12> for PB15<Alternate<AF9>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM12> for PH6<Alternate<AF9>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2<TIM12> for PH9<Alternate<AF9>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM13> for PA6<Alternate<AF9>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM13> for PF8<Alternate<AF9>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM14> for PA7<Alternate<AF9>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1<TIM14> for PF9<Alternate<AF9>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1N<TIM1> for PA7<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1N<TIM1> for PB13<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2N<TIM1> for PB0<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2N<TIM1> for PB14<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3N<TIM1> for PB1<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3N<TIM1> for PB15<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinBkin<TIM1> for PA6<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinBkin<TIM1> for PB12<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1N<TIM1> for PE8<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2N<TIM1> for PE10<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3N<TIM1> for PE12<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinBkin<TIM1> for PE15<Alternate<AF1>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1N<TIM8> for PA5<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1N<TIM8> for PA7<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2N<TIM8> for PB0<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2N<TIM8> for PB14<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3N<TIM8> for PB1<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3N<TIM8> for PB15<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinBkin<TIM8> for PA6<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC1N<TIM8> for PH13<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC2N<TIM8> for PH14<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinC3N<TIM8> for PH15<Alternate<AF3>> {}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinBkin<TIM8> for PI4<Alternate<AF3>> {}
@@ -1,6 +1,6 @@
 //! # Quadrature Encoder Interface
 use crate::hal::{self, Direction};
-use crate::stm32::RCC;
+use crate::rcc::{Enable, Reset};
 
 use crate::gpio::gpioa::*;
 #[cfg(any(
@@ -790,7 +790,7 @@ pub struct Qei<TIM, PINS> {
 }
 
 macro_rules! hal {
-    ($($TIM:ident: ($tim:ident, $timXen:ident, $timXrst:ident, $apbenr:ident, $apbrstr:ident, $bits:ident),)+) => {
+    ($($TIM:ident: ($tim:ident, $bits:ident),)+) => {
         $(
             impl<PINS> Qei<$TIM, PINS> {
                 /// Configures a TIM peripheral as a quadrature encoder interface input
@@ -798,11 +798,9 @@ macro_rules! hal {
                 where
                     PINS: Pins<$TIM>
                 {
-                    let rcc = unsafe { &(*RCC::ptr()) };
                     // enable and reset peripheral to a clean slate state
-                    rcc.$apbenr.modify(|_, w| w.$timXen().set_bit());
-                    rcc.$apbrstr.modify(|_, w| w.$timXrst().set_bit());
-                    rcc.$apbrstr.modify(|_, w| w.$timXrst().clear_bit());
+                    $TIM::enable();
+                    $TIM::reset();
 
                     // Configure TxC1 and TxC2 as captures
                     tim.ccmr1_output()
@@ -877,8 +875,8 @@ macro_rules! hal {
     feature = "stm32f479"
 ))]
 hal! {
-    TIM1: (tim1, tim1en, tim1rst, apb2enr, apb2rstr, u16),
-    TIM5: (tim5, tim5en, tim5rst, apb1enr, apb1rstr, u32),
+    TIM1: (tim1, u16),
+    TIM5: (tim5, u32),
 }
 
 #[cfg(any(
@@ -900,9 +898,9 @@ hal! {
     feature = "stm32f479"
 ))]
 hal! {
-    TIM2: (tim2, tim2en, tim2rst, apb1enr, apb1rstr, u32),
-    TIM3: (tim3, tim3en, tim3rst, apb1enr, apb1rstr, u16),
-    TIM4: (tim4, tim4en, tim4rst, apb1enr, apb1rstr, u16),
+    TIM2: (tim2, u32),
+    TIM3: (tim3, u16),
+    TIM4: (tim4, u16),
 }
 
 #[cfg(any(
@@ -922,5 +920,5 @@ hal! {
     feature = "stm32f479"
 ))]
 hal! {
-    TIM8: (tim8, tim8en, tim8rst, apb2enr, apb2rstr, u16),
+    TIM8: (tim8, u16),
 }
@@ -1,8 +1,15 @@
-use crate::stm32::RCC;
+use core::ops::RangeInclusive;
+
+use crate::stm32::{FLASH, PWR, RCC};
 use crate::stm32::rcc::cfgr::{HPRE_A, SW_A};
 
+use crate::gpio::gpioa::PA8;
+use crate::gpio::gpioc::PC9;
+use crate::gpio::{Alternate, Speed, AF0};
 use crate::time::Hertz;
 
+mod enable;
+
 /// Extension trait that constrains the `RCC` peripheral
 pub trait RccExt {
     /// Constrains the `RCC` peripheral so it plays nicely with the other abstractions
@@ -11,15 +18,108 @@ pub trait RccExt {
 
 impl RccExt for RCC {
     fn constrain(self) -> Rcc {
-        Rcc {
-            cfgr: CFGR {
-                hse: None,
-                hclk: None,
-                pclk1: None,
-                pclk2: None,
-                sysclk: None,
-            },
-        }
+        Rcc { cfgr: CFGR::new() }
+    }
+}
+
+/// Enables and disables the clock of a peripheral
+pub trait Enable {
+    /// Enables the clock of the peripheral
+    fn enable();
+
+    /// Disables the clock of the peripheral
+    fn disable();
+
+    /// Returns whether the clock of the peripheral is enabled
+    fn is_enabled() -> bool;
+}
+
+/// Enables and disables the clock of a peripheral in sleep mode
+pub trait LPEnable {
+    /// Keeps the clock of the peripheral running in sleep mode, the reset default
+    fn low_power_enable();
+
+    /// Stops the clock of the peripheral in sleep mode
+    fn low_power_disable();
+
+    /// Returns whether the clock of the peripheral keeps running in sleep mode
+    fn is_low_power_enabled() -> bool;
+}
+
+/// Resets a peripheral
+pub trait Reset {
+    /// Resets all registers of the peripheral
+    ///
+    /// ADC1, ADC2 and ADC3 share a single reset, resetting one of them resets all.
+    fn reset();
+}
+
+/// Frequency of the bus a peripheral is connected to
+pub trait BusClock {
+    /// Returns the frequency of the bus clock
+    fn clock(clocks: &Clocks) -> Hertz;
+}
+
+/// Frequency of the timer clock of an APB
+pub trait BusTimerClock {
+    /// Returns the frequency of the timer clock, see `Clocks::timclk1` and `Clocks::timclk2`
+    fn timer_clock(clocks: &Clocks) -> Hertz;
+}
+
+/// AMBA High-performance Bus 1
+pub struct AHB1;
+
+impl BusClock for AHB1 {
+    fn clock(clocks: &Clocks) -> Hertz {
+        clocks.hclk
+    }
+}
+
+/// AMBA High-performance Bus 2
+pub struct AHB2;
+
+impl BusClock for AHB2 {
+    fn clock(clocks: &Clocks) -> Hertz {
+        clocks.hclk
+    }
+}
+
+/// AMBA High-performance Bus 3
+pub struct AHB3;
+
+impl BusClock for AHB3 {
+    fn clock(clocks: &Clocks) -> Hertz {
+        clocks.hclk
+    }
+}
+
+/// Advanced Peripheral Bus 1
+pub struct APB1;
+
+impl BusClock for APB1 {
+    fn clock(clocks: &Clocks) -> Hertz {
+        clocks.pclk1
+    }
+}
+
+impl BusTimerClock for APB1 {
+    fn timer_clock(clocks: &Clocks) -> Hertz {
+        clocks.timclk1
+    }
+}
+
+/// Advanced Peripheral Bus 2
+pub struct APB2;
+
+impl BusClock for APB2 {
+    fn clock(clocks: &Clocks) -> Hertz {
+        clocks.pclk2
+    }
+}
+
+impl BusTimerClock for APB2 {
+    fn timer_clock(clocks: &Clocks) -> Hertz {
+        clocks.timclk2
     }
 }
 
@@ -29,18 +129,587 @@ pub struct Rcc {
 }
 
 const HSI: u32 = 16_000_000; // Hz
+const LSE: u32 = 32_768; // Hz
+const LSI: u32 = 32_000; // Hz
 
+/// Number of times the ready flag of an oscillator or PLL is polled before giving up
+const READY_TIMEOUT: u32 = 1_000_000;
+// LSE crystals take up to a few seconds to start
+const LSE_READY_TIMEOUT: u32 = 50_000_000;
+
+#[derive(Clone)]
 pub struct CFGR {
     hse: Option<u32>,
+    hse_bypass: bool,
+    css: bool,
     hclk: Option<u32>,
     pclk1: Option<u32>,
     pclk2: Option<u32>,
     sysclk: Option<u32>,
+    pll48clk: bool,
+    i2sclk: Option<u32>,
+    i2s_ckin: Option<u32>,
+    saiclk: Option<u32>,
+    lse: Option<u32>,
+    lse_bypass: bool,
+    #[cfg(any(
+        feature = "stm32f401",
+        feature = "stm32f410",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f423",
+        feature = "stm32f446"
+    ))]
+    lse_drive: Option<LseDrive>,
+    lsi: bool,
+    mco1: Option<(Mco1, McoPre)>,
+    mco2: Option<(Mco2, McoPre)>,
+    voltage: SupplyVoltage,
+    timpre: bool,
+    exact: bool,
+}
+
+/// Range of the supply voltage (VDD), which determines the flash wait states
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SupplyVoltage {
+    V1_8To2_1,
+    V2_1To2_4,
+    V2_4To2_7,
+    /// The default
+    V2_7To3_6,
+}
+
+/// Drive strength of the LSE oscillator
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423",
+    feature = "stm32f446"
+))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LseDrive {
+    /// Low power mode, the reset default
+    Low,
+    /// High drive mode, for crystals that don't start in low power mode
+    High,
+}
+
+/// Clock source of the MCO1 output on PA8
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mco1 {
+    Hsi = 0b00,
+    Lse = 0b01,
+    Hse = 0b10,
+    Pll = 0b11,
+}
+
+/// Clock source of the MCO2 output on PC9
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mco2 {
+    Sysclk = 0b00,
+    #[cfg(any(
+        feature = "stm32f401",
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f415",
+        feature = "stm32f417",
+        feature = "stm32f423",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    Plli2s = 0b01,
+    Hse = 0b10,
+    Pll = 0b11,
+}
+
+/// Prescaler of the MCO outputs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum McoPre {
+    Div1 = 0b000,
+    Div2 = 0b100,
+    Div3 = 0b101,
+    Div4 = 0b110,
+    Div5 = 0b111,
+}
+
+/// Clock signals generated by the RCC
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Clock {
+    Sysclk,
+    Hclk,
+    Pclk1,
+    Pclk2,
+    Pll48clk,
+    I2sclk,
+    Saiclk,
+    Hsi,
+    Hse,
+    Lsi,
+    Lse,
+    Pll,
+    Plli2s,
+    Pllsai,
+    /// The over-drive mode of the regulator, only reported by `ClockError::Timeout`
+    OverDrive,
+}
+
+/// Reason a clock configuration can't be applied
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClockError {
+    /// The PLL source clock can't be divided down into the 1 - 2 MHz PLL input range
+    PllInput { pllsrcclk: u32 },
+    /// A frequency is outside of the range the device supports
+    OutOfRange {
+        clock: Clock,
+        requested: u32,
+        min: u32,
+        max: u32,
+    },
+    /// A frequency can't be generated exactly, see `CFGR::require_exact`
+    NotExact {
+        clock: Clock,
+        requested: u32,
+        achieved: u32,
+    },
+    /// An oscillator or PLL didn't become ready, it has been switched off again, or didn't stop
+    ///
+    /// `Clock::Sysclk` means the system clock switch didn't take effect.
+    Timeout { clock: Clock },
+}
+
+/// Polls `ready` until it returns `true`, or returns `ClockError::Timeout`
+fn wait_ready<F>(clock: Clock, ready: F) -> Result<(), ClockError>
+where
+    F: Fn() -> bool,
+{
+    let timeout = match clock {
+        Clock::Lse => LSE_READY_TIMEOUT,
+        _ => READY_TIMEOUT,
+    };
+    for _ in 0..timeout {
+        if ready() {
+            return Ok(());
+        }
+    }
+    Err(ClockError::Timeout { clock })
+}
+
+/// Dividers of the main PLL
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PllConfig {
+    /// Input divider, the PLL input is the PLL source clock divided by `m`
+    pub m: u32,
+    /// VCO multiplier
+    pub n: u32,
+    /// Divider of the system clock output
+    pub p: u32,
+    /// Divider of the 48 MHz output
+    pub q: u32,
+}
+
+impl PllConfig {
+    fn vco(&self, pllsrcclk: u32) -> u32 {
+        (u64::from(pllsrcclk) * u64::from(self.n) / u64::from(self.m)) as u32
+    }
+}
+
+/// Dividers of the PLLI2S or PLLSAI, only the outputs in use are set
+// Not every device has all of these outputs
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Default)]
+struct AltPll {
+    m: u32,
+    n: u32,
+    p: Option<u32>,
+    q: Option<u32>,
+    // PLLSAIDIVQ divider following the Q output for the SAI clock
+    divq: Option<u32>,
+    r: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PllSetup {
+    sysclk: u32,
+    // sysclk is taken from the main PLL
+    use_pll: bool,
+    main: Option<PllConfig>,
+    // input divider of the main PLL if it is only needed by the PLLI2S or PLLSAI
+    pllm: Option<u32>,
+    plli2s: Option<AltPll>,
+    pllsai: Option<AltPll>,
+    pll48clk: Option<u32>,
+    // PLL48CLK is taken from the PLLI2S or PLLSAI instead of the main PLL
+    pll48_alt: bool,
+    i2sclk: Option<u32>,
+    saiclk: Option<u32>,
+}
+
+// Devices that can take PLL48CLK from the Q output of the PLLI2S
+const PLL48_PLLI2S: bool = cfg!(any(
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423"
+));
+
+// Devices that can take PLL48CLK from the P output of the PLLSAI
+const PLL48_PLLSAI: bool = cfg!(any(
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+));
+
+// Devices with a separate PLLI2SM divider, the others share PLLM with the main PLL
+const PLLI2S_OWN_M: bool = cfg!(any(
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423",
+    feature = "stm32f446"
+));
+
+// Devices with a separate PLLSAIM divider, the others share PLLM with the main PLL
+const PLLSAI_OWN_M: bool = cfg!(feature = "stm32f446");
+
+/// Valid input dividers for a PLL, resulting in a frequency from 1 to 2 MHz
+fn pllm_range(pllsrcclk: u32) -> RangeInclusive<u32> {
+    core::cmp::max(2, (pllsrcclk + 1_999_999) / 2_000_000)..=core::cmp::min(63, pllsrcclk / 1_000_000)
+}
+
+/// Finds the main PLL dividers that generate a system clock as close as possible to, but not
+/// above `sysclk`
+///
+/// With `pll48clk` the dividers that generate the best system clock are searched for one that
+/// also generates 48 MHz on the Q output. The system clock is never lowered for this, the
+/// caller falls back to another PLL if the Q output is off.
+fn main_pll(pllsrcclk: u32, sysclk: u32, pll48clk: bool) -> Option<PllConfig> {
+    // Sysclk output divisor must be one of 2, 4, 6 or 8, the largest one that keeps the VCO
+    // output below 432 MHz is preferred
+    let sysclk_div = core::cmp::min(8, (432_000_000 / sysclk) & !1);
+    let pllp = (2..=sysclk_div).rev().step_by(2).take(if pll48clk { 4 } else { 1 });
+
+    // (sysclk error, PLL48CLK error) and dividers of the best solution
+    let mut best: Option<((u32, u32), PllConfig)> = None;
+
+    // Find the lowest pllm value that minimize the difference between
+    // requested sysclk and actual sysclk.
+    for m in pllm_range(pllsrcclk) {
+        for p in pllp.clone() {
+            // Main scaler, must result in >= 100MHz (>= 192MHz for F401)
+            // and <= 432MHz, min 50, max 432
+            let n = (u64::from(sysclk) * u64::from(p * m) / u64::from(pllsrcclk)) as u32;
+            if n < 50 || n > 432 {
+                continue;
+            }
+
+            let mut pll = PllConfig { m, n, p, q: 2 };
+            let vco = pll.vco(pllsrcclk);
+            if vco < 100_000_000 {
+                continue;
+            }
+
+            // Keep PLL48CLK at or below 48 MHz unless it has to be exact
+            pll.q = if pll48clk {
+                (vco + 24_000_000) / 48_000_000
+            } else {
+                (vco + 47_999_999) / 48_000_000
+            };
+            pll.q = core::cmp::min(15, core::cmp::max(2, pll.q));
+
+            let pll48_error = if pll48clk {
+                (i64::from(vco / pll.q) - 48_000_000).abs() as u32
+            } else {
+                0
+            };
+            let key = (sysclk - vco / p, pll48_error);
+
+            if best.map_or(true, |(best_key, _)| key < best_key) {
+                best = Some((key, pll));
+            }
+        }
+    }
+
+    best.map(|(_, pll)| pll)
+}
+
+/// Output divider of a PLL
+#[derive(Clone, Copy)]
+enum Divider {
+    /// 2, 4, 6 or 8
+    P,
+    /// 2 to 15
+    Q,
+    /// 2 to 7
+    R,
+    /// 2 to 15, followed by the 1 to 32 PLLSAIDIVQ divider of the SAI clock
+    QDivQ,
+}
+
+impl Divider {
+    /// Returns the divider values that bring `vco` closest to `target` and the resulting
+    /// frequency
+    fn best(self, vco: u32, target: u32) -> ((u32, u32), u32) {
+        let closest = |min: u32, max: u32, step: u32| {
+            let div = (vco + target * step / 2) / (target * step) * step;
+            core::cmp::min(max, core::cmp::max(min, div))
+        };
+
+        match self {
+            Divider::P => {
+                let p = closest(2, 8, 2);
+                ((p, 1), vco / p)
+            }
+            Divider::Q => {
+                let q = closest(2, 15, 1);
+                ((q, 1), vco / q)
+            }
+            Divider::R => {
+                let r = closest(2, 7, 1);
+                ((r, 1), vco / r)
+            }
+            Divider::QDivQ => (2..=15)
+                .map(|q| {
+                    let divq = (vco / q + target / 2) / target;
+                    let divq = core::cmp::min(32, core::cmp::max(1, divq));
+                    ((q, divq), vco / q / divq)
+                })
+                .min_by_key(|&(_, freq)| (i64::from(freq) - i64::from(target)).abs())
+                .unwrap(),
+        }
+    }
+}
+
+/// A frequency a PLL output has to generate
+#[derive(Clone, Copy)]
+struct PllTarget {
+    divider: Divider,
+    freq: u32,
+    exact: bool,
+}
+
+/// Finds the input divider within `pllm`, the multiplier and the output dividers of a PLL that
+/// generate frequencies as close as possible to `targets`
+///
+/// Exact targets have to be met exactly. Returns the input divider, the multiplier and the
+/// divider values and frequency of each target.
+fn pll_search(
+    pllsrcclk: u32,
+    pllm: RangeInclusive<u32>,
+    targets: &[PllTarget],
+) -> Option<(u32, u32, [((u32, u32), u32); 2])> {
+    // sum of the relative errors in ppm and the best solution
+    let mut best: Option<(u64, (u32, u32, [((u32, u32), u32); 2]))> = None;
+
+    for m in pllm {
+        for n in 50..=432 {
+            let vco = (u64::from(pllsrcclk) * n / u64::from(m)) as u32;
+            if vco < 100_000_000 || vco > 432_000_000 {
+                continue;
+            }
+
+            let mut outputs = [((0, 0), 0); 2];
+            let mut error = 0;
+            let mut valid = true;
+            for (output, target) in outputs.iter_mut().zip(targets) {
+                *output = target.divider.best(vco, target.freq);
+
+                let diff = (i64::from(output.1) - i64::from(target.freq)).abs() as u64;
+                valid &= !target.exact || diff == 0;
+                error += diff * 1_000_000 / u64::from(target.freq);
+            }
+
+            if valid && best.map_or(true, |(best_error, _)| error < best_error) {
+                best = Some((error, (m, n as u32, outputs)));
+                if error == 0 {
+                    return best.map(|(_, solution)| solution);
+                }
+            }
+        }
+    }
+
+    best.map(|(_, solution)| solution)
+}
+
+/// Error for a PLLI2S or PLLSAI without solution for `targets`, which generate `clocks`
+///
+/// Reports the first exact target that is missed with the closest frequency the PLL can
+/// generate for it.
+fn alt_pll_error(
+    pllsrcclk: u32,
+    pllm: RangeInclusive<u32>,
+    targets: &[PllTarget],
+    clocks: &[Clock; 2],
+) -> ClockError {
+    let mut approximate = [targets[0]; 2];
+    for (target, approx) in targets.iter().zip(approximate.iter_mut()) {
+        *approx = PllTarget {
+            exact: false,
+            ..*target
+        };
+    }
+
+    let (_, _, outputs) = match pll_search(pllsrcclk, pllm, &approximate[..targets.len()]) {
+        Some(solution) => solution,
+        None => return ClockError::PllInput { pllsrcclk },
+    };
+    targets
+        .iter()
+        .zip(outputs.iter())
+        .zip(clocks.iter())
+        .find(|((target, output), _)| target.exact && output.1 != target.freq)
+        .map_or(ClockError::PllInput { pllsrcclk }, |((target, output), &clock)| {
+            ClockError::NotExact {
+                clock,
+                requested: target.freq,
+                achieved: output.1,
+            }
+        })
+}
+
+/// Input dividers the PLLI2S can use
+fn plli2s_m(pllsrcclk: u32, pllm: Option<u32>) -> RangeInclusive<u32> {
+    if PLLI2S_OWN_M {
+        pllm_range(pllsrcclk)
+    } else {
+        pllm.map_or_else(|| pllm_range(pllsrcclk), |m| m..=m)
+    }
+}
+
+/// Input dividers the PLLSAI can use
+fn pllsai_m(pllsrcclk: u32, pllm: Option<u32>) -> RangeInclusive<u32> {
+    if PLLSAI_OWN_M {
+        pllm_range(pllsrcclk)
+    } else {
+        pllm.map_or_else(|| pllm_range(pllsrcclk), |m| m..=m)
+    }
+}
+
+/// Returns the clock of the timers on an APB
+fn timer_clock(hclk: u32, pclk: u32, ppre: u8, timpre: bool) -> u32 {
+    match (timpre, ppre) {
+        (false, 1) => pclk,
+        (false, _) => 2 * pclk,
+        (true, 1) | (true, 2) | (true, 4) => hclk,
+        (true, _) => 4 * pclk,
+    }
+}
+
+/// Returns the mask and value of the VOS bits in PWR_CR for the lowest regulator voltage scale
+/// that supports `sysclk`
+fn voltage_scale(sysclk: u32) -> (u32, u32) {
+    #[cfg(any(
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f415",
+        feature = "stm32f417"
+    ))]
+    let (mask, vos) = (1 << 14, if sysclk <= 144_000_000 { 0 } else { 1 << 14 });
+
+    #[cfg(any(
+        feature = "stm32f401"
+    ))]
+    let (mask, vos) = (0b11 << 14, if sysclk <= 60_000_000 { 0b01 << 14 } else { 0b10 << 14 });
+
+    #[cfg(any(
+        feature = "stm32f410",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f423"
+    ))]
+    let (mask, vos) = (
+        0b11 << 14,
+        match sysclk {
+            0..=64_000_000 => 0b01 << 14,
+            64_000_001..=84_000_000 => 0b10 << 14,
+            _ => 0b11 << 14,
+        },
+    );
+
+    #[cfg(any(
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    let (mask, vos) = (
+        0b11 << 14,
+        match sysclk {
+            0..=120_000_000 => 0b01 << 14,
+            120_000_001..=144_000_000 => 0b10 << 14,
+            _ => 0b11 << 14,
+        },
+    );
+
+    (mask, vos)
+}
+
+/// Deviation of `achieved` from `requested` in parts per million
+fn error_ppm(requested: u32, achieved: u32) -> i32 {
+    ((i64::from(achieved) - i64::from(requested)) * 1_000_000 / i64::from(requested)) as i32
+}
+
+impl Default for CFGR {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CFGR {
+    /// Creates the default configuration: HSI as the system clock without any prescaler
+    ///
+    /// This doesn't touch the hardware, so it can be used to compute a `plan` on the host.
+    /// `RccExt::constrain` is the way to get a `CFGR` that is applied to the device.
+    pub fn new() -> Self {
+        CFGR {
+            hse: None,
+            hse_bypass: false,
+            css: false,
+            hclk: None,
+            pclk1: None,
+            pclk2: None,
+            sysclk: None,
+            pll48clk: false,
+            i2sclk: None,
+            i2s_ckin: None,
+            saiclk: None,
+            lse: None,
+            lse_bypass: false,
+            #[cfg(any(
+                feature = "stm32f401",
+                feature = "stm32f410",
+                feature = "stm32f411",
+                feature = "stm32f412",
+                feature = "stm32f413",
+                feature = "stm32f423",
+                feature = "stm32f446"
+            ))]
+            lse_drive: None,
+            lsi: false,
+            mco1: None,
+            mco2: None,
+            voltage: SupplyVoltage::V2_7To3_6,
+            timpre: false,
+            exact: false,
+        }
+    }
+
     /// Uses HSE (external oscillator) instead of HSI (internal RC oscillator) as the clock source.
-    /// Will result in a hang if an external oscillator is not connected or it fails to start.
+    /// `try_freeze` returns `ClockError::Timeout` if the oscillator fails to start.
     pub fn use_hse<F>(mut self, freq: F) -> Self
     where
         F: Into<Hertz>,
@@ -49,6 +718,37 @@ impl CFGR {
         self
     }
 
+    /// Uses an external clock signal on OSC_IN as HSE instead of a crystal or resonator, the
+    /// frequency is set with `use_hse`
+    pub fn bypass_hse_oscillator(mut self) -> Self {
+        self.hse_bypass = true;
+        self
+    }
+
+    /// Enables the Clock Security System, which switches the system clock to HSI when the HSE
+    /// fails
+    ///
+    /// A failure raises the NMI, whose handler has to call `handle_css_nmi`. Has no effect
+    /// without `use_hse`.
+    pub fn enable_css(mut self) -> Self {
+        self.css = true;
+        self
+    }
+
+    /// Outputs a clock on PA8 (MCO1), the pin is switched to very high speed
+    pub fn mco1(mut self, pin: PA8<Alternate<AF0>>, source: Mco1, prescaler: McoPre) -> Self {
+        pin.set_speed(Speed::VeryHigh);
+        self.mco1 = Some((source, prescaler));
+        self
+    }
+
+    /// Outputs a clock on PC9 (MCO2), the pin is switched to very high speed
+    pub fn mco2(mut self, pin: PC9<Alternate<AF0>>, source: Mco2, prescaler: McoPre) -> Self {
+        pin.set_speed(Speed::VeryHigh);
+        self.mco2 = Some((source, prescaler));
+        self
+    }
+
     pub fn hclk<F>(mut self, freq: F) -> Self
     where
         F: Into<Hertz>,
@@ -81,72 +781,356 @@ impl CFGR {
         self
     }
 
-    fn pll_setup(&self) -> (bool, u32)
+    /// Enables the LSE oscillator with a 32.768 kHz crystal, used by the RTC
+    ///
+    /// `try_freeze` enables write access to the backup domain for this, see
+    /// `enable_backup_domain_access`, and returns `ClockError::Timeout` if the LSE doesn't start,
+    /// e.g. because no crystal is fitted.
+    pub fn use_lse(mut self) -> Self {
+        self.lse = Some(LSE);
+        self.lse_bypass = false;
+        self
+    }
+
+    /// Enables the LSE with an external clock of up to 1 MHz on OSC32_IN instead of a crystal
+    pub fn use_lse_bypass<F>(mut self, freq: F) -> Self
+    where
+        F: Into<Hertz>,
     {
-        let rcc = unsafe { &*RCC::ptr() };
+        self.lse = Some(freq.into().0);
+        self.lse_bypass = true;
+        self
+    }
+
+    /// Sets the drive strength of the LSE oscillator
+    #[cfg(any(
+        feature = "stm32f401",
+        feature = "stm32f410",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f423",
+        feature = "stm32f446"
+    ))]
+    pub fn lse_drive(mut self, drive: LseDrive) -> Self {
+        self.lse_drive = Some(drive);
+        self
+    }
+
+    /// Enables the 32 kHz LSI RC oscillator, used by the IWDG and optionally the RTC
+    pub fn use_lsi(mut self) -> Self {
+        self.lsi = true;
+        self
+    }
+
+    /// Sets the supply voltage range, the default is 2.7 - 3.6 V
+    ///
+    /// Lower voltages need more flash wait states, and on devices with over-drive limit the
+    /// system clock to 168 MHz.
+    pub fn supply_voltage(mut self, voltage: SupplyVoltage) -> Self {
+        self.voltage = voltage;
+        self
+    }
+
+    /// Sets TIMPRE, which clocks the timers with HCLK if the APB prescaler is 1, 2 or 4, and
+    /// with four times the APB clock otherwise
+    ///
+    /// Without TIMPRE the timer clock is the APB clock if the APB prescaler is 1, and twice the
+    /// APB clock otherwise.
+    #[cfg(any(
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    pub fn timpre(mut self, timpre: bool) -> Self {
+        self.timpre = timpre;
+        self
+    }
+
+    /// Requires all explicitly requested frequencies to be met exactly, `try_freeze` returns
+    /// `ClockError::NotExact` instead of using the closest achievable frequency.
+    pub fn require_exact(mut self) -> Self {
+        self.exact = true;
+        self
+    }
+
+    fn check_exact(
+        &self,
+        clock: Clock,
+        requested: Option<u32>,
+        achieved: u32,
+    ) -> Result<(), ClockError> {
+        match requested {
+            Some(requested) if self.exact && requested != achieved => Err(ClockError::NotExact {
+                clock,
+                requested,
+                achieved,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Requires a 48 MHz clock for USB OTG FS, SDIO and RNG
+    ///
+    /// The main PLL is set up for the system clock first, if its Q output can generate exactly
+    /// 48 MHz as well it is used. Otherwise the PLLSAI (F446, F469, F479) or PLLI2S (F412, F413,
+    /// F423) generates the 48 MHz clock instead. `try_freeze` fails with `ClockError::NotExact`
+    /// if neither works.
+    pub fn require_pll48clk(mut self) -> Self {
+        self.pll48clk = true;
+        self
+    }
+
+    /// Sets the frequency of the I2S clock generated by the PLLI2S, usually 256 times the
+    /// sample rate
+    ///
+    /// `Clocks::i2sclk_error_ppm` returns how far the sample rate will be off.
+    #[cfg(any(
+        feature = "stm32f401",
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f415",
+        feature = "stm32f417",
+        feature = "stm32f423",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    pub fn i2s_clk<F>(mut self, freq: F) -> Self
+    where
+        F: Into<Hertz>,
+    {
+        self.i2sclk = Some(freq.into().0);
+        self
+    }
+
+    /// Uses the external clock on the I2S_CKIN pin with the given frequency as I2S clock
+    #[cfg(any(
+        feature = "stm32f401",
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f415",
+        feature = "stm32f417",
+        feature = "stm32f423",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    pub fn i2s_ckin<F>(mut self, freq: F) -> Self
+    where
+        F: Into<Hertz>,
+    {
+        self.i2s_ckin = Some(freq.into().0);
+        self
+    }
+
+    /// Sets the frequency of the SAI clock generated by the PLLSAI
+    ///
+    /// `Clocks::saiclk_error_ppm` returns how far the sample rate will be off.
+    #[cfg(any(
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    pub fn sai_clk<F>(mut self, freq: F) -> Self
+    where
+        F: Into<Hertz>,
+    {
+        self.saiclk = Some(freq.into().0);
+        self
+    }
 
+    fn pll_setup(&self) -> Result<PllSetup, ClockError> {
         let pllsrcclk = self.hse.unwrap_or(HSI);
         let sysclk = self.sysclk.unwrap_or(pllsrcclk);
+        let use_pll = sysclk != pllsrcclk;
+
+        let pll48_target = PllTarget {
+            divider: Divider::Q,
+            freq: 48_000_000,
+            exact: true,
+        };
 
-        // Sysclk output divisor must be one of 2, 4, 6 or 8
-        let sysclk_div = core::cmp::min(8, (432_000_000 / sysclk) & !1);
+        let mut main = if use_pll {
+            Some(
+                main_pll(pllsrcclk, sysclk, self.pll48clk)
+                    .ok_or(ClockError::PllInput { pllsrcclk })?,
+            )
+        } else if self.pll48clk {
+            // The main PLL only generates PLL48CLK
+            pll_search(pllsrcclk, pllm_range(pllsrcclk), &[pll48_target]).map(
+                |(m, n, [((q, _), _), _])| PllConfig { m, n, p: 8, q },
+            )
+        } else {
+            None
+        };
 
-        // Input divisor from PLL source clock, must result to frequency in
-        // the range from 1 to 2 MHz
-        let pllm_min = (pllsrcclk + 1_999_999) / 2_000_000;
-        let pllm_max = pllsrcclk / 1_000_000;
+        let mut pll48clk = main.map(|pll| pll.vco(pllsrcclk) / pll.q);
+        let pll48_alt = self.pll48clk && pll48clk != Some(48_000_000);
+        if pll48_alt {
+            if !PLL48_PLLI2S && !PLL48_PLLSAI {
+                return Err(ClockError::NotExact {
+                    clock: Clock::Pll48clk,
+                    requested: 48_000_000,
+                    achieved: pll48clk.unwrap_or(0),
+                });
+            }
 
-        // Find the lowest pllm value that minimize the difference between
-        // requested sysclk and actual sysclk.
-        let pllm = (pllm_min..=pllm_max).min_by_key(|pllm| {
-            let vco_in = pllsrcclk / pllm;
-            let plln = sysclk * sysclk_div / vco_in;
-            sysclk - (vco_in * plln / sysclk_div)
-        }).unwrap();
+            // Optimise the main PLL for the system clock only
+            main = if use_pll {
+                main_pll(pllsrcclk, sysclk, false)
+            } else {
+                None
+            };
+            pll48clk = Some(48_000_000);
+        }
 
-        let vco_in = pllsrcclk / pllm;
-        assert!(vco_in >= 1_000_000 && vco_in <= 2_000_000);
+        let mut pllm = main.map(|pll| pll.m);
 
-        // Main scaler, must result in >= 100MHz (>= 192MHz for F401)
-        // and <= 432MHz, min 50, max 432
-        let plln = sysclk * sysclk_div / vco_in;
+        // PLLI2S: I2S clock on R, PLL48CLK on Q
+        let mut targets = [pll48_target; 2];
+        let mut clocks = [Clock::Pll48clk; 2];
+        let mut len = 0;
+        let i2s_target = self.i2sclk.filter(|_| self.i2s_ckin.is_none());
+        if let Some(freq) = i2s_target {
+            targets[len] = PllTarget {
+                divider: Divider::R,
+                freq,
+                exact: self.exact,
+            };
+            clocks[len] = Clock::I2sclk;
+            len += 1;
+        }
+        if pll48_alt && PLL48_PLLI2S {
+            targets[len] = pll48_target;
+            clocks[len] = Clock::Pll48clk;
+            len += 1;
+        }
 
-        let pllp = (sysclk_div / 2) - 1;
+        let mut plli2s = None;
+        let mut i2sclk = self.i2s_ckin;
+        if len > 0 {
+            let plli2sm = plli2s_m(pllsrcclk, pllm);
+            let (m, n, outputs) = pll_search(pllsrcclk, plli2sm.clone(), &targets[..len])
+                .ok_or_else(|| alt_pll_error(pllsrcclk, plli2sm, &targets[..len], &clocks))?;
 
-        // Calculate real system clock
-        let sysclk = vco_in * plln / sysclk_div;
+            let mut pll = AltPll {
+                m,
+                n,
+                ..AltPll::default()
+            };
+            let mut outputs = outputs.iter();
+            if i2s_target.is_some() {
+                let &((r, _), freq) = outputs.next().unwrap();
+                pll.r = Some(r);
+                i2sclk = Some(freq);
+            }
+            if pll48_alt && PLL48_PLLI2S {
+                pll.q = Some((outputs.next().unwrap().0).0);
+            }
 
-        if sysclk != pllsrcclk {
-            // use PLL as source
-            rcc.pllcfgr.write(|w| unsafe {
-                w.pllm()
-                    .bits(pllm as u8)
-                    .plln()
-                    .bits(plln as u16)
-                    .pllp()
-                    .bits(pllp as u8)
-                    .pllsrc()
-                    .bit(self.hse.is_some())
-            });
+            pllm = pllm.or(Some(m));
+            plli2s = Some(pll);
+        }
 
-            (true, sysclk)
-        } else {
-            (false, pllsrcclk)
+        // PLLSAI: SAI clock on Q, PLL48CLK on P
+        let mut len = 0;
+        if let Some(freq) = self.saiclk {
+            targets[len] = PllTarget {
+                divider: Divider::QDivQ,
+                freq,
+                exact: self.exact,
+            };
+            clocks[len] = Clock::Saiclk;
+            len += 1;
         }
-    }
+        if pll48_alt && PLL48_PLLSAI {
+            targets[len] = PllTarget {
+                divider: Divider::P,
+                ..pll48_target
+            };
+            clocks[len] = Clock::Pll48clk;
+            len += 1;
+        }
+
+        let mut pllsai = None;
+        let mut saiclk = None;
+        if len > 0 {
+            let pllsaim = pllsai_m(pllsrcclk, pllm);
+            let (m, n, outputs) = pll_search(pllsrcclk, pllsaim.clone(), &targets[..len])
+                .ok_or_else(|| alt_pll_error(pllsrcclk, pllsaim, &targets[..len], &clocks))?;
+
+            let mut pll = AltPll {
+                m,
+                n,
+                ..AltPll::default()
+            };
+            let mut outputs = outputs.iter();
+            if self.saiclk.is_some() {
+                let &((q, divq), freq) = outputs.next().unwrap();
+                pll.q = Some(q);
+                pll.divq = Some(divq);
+                saiclk = Some(freq);
+            }
+            if pll48_alt && PLL48_PLLSAI {
+                pll.p = Some((outputs.next().unwrap().0).0);
+            }
 
-    fn flash_setup(sysclk: u32) {
-        use crate::stm32::FLASH;
+            pllm = pllm.or(Some(m));
+            pllsai = Some(pll);
+        }
+
+        Ok(PllSetup {
+            sysclk: match main {
+                Some(pll) if use_pll => pll.vco(pllsrcclk) / pll.p,
+                _ => pllsrcclk,
+            },
+            use_pll,
+            main,
+            pllm,
+            plli2s,
+            pllsai,
+            pll48clk,
+            pll48_alt,
+            i2sclk,
+            saiclk,
+        })
+    }
 
+    /// Stops the PLLI2S
+    fn plli2s_disable() -> Result<(), ClockError> {
         #[cfg(any(
             feature = "stm32f401",
             feature = "stm32f405",
             feature = "stm32f407",
-            feature = "stm32f410",
             feature = "stm32f411",
             feature = "stm32f412",
+            feature = "stm32f413",
             feature = "stm32f415",
             feature = "stm32f417",
+            feature = "stm32f423",
             feature = "stm32f427",
             feature = "stm32f429",
             feature = "stm32f437",
@@ -155,33 +1139,23 @@ impl CFGR {
             feature = "stm32f469",
             feature = "stm32f479"
         ))]
-        let flash_latency_step = 30_000_000;
-
-        #[cfg(any(feature = "stm32f413", feature = "stm32f423"))]
-        let flash_latency_step = 25_000_000;
+        {
+            let rcc = unsafe { &*RCC::ptr() };
 
-        unsafe {
-            let flash = &(*FLASH::ptr());
-            // Adjust flash wait states
-            flash.acr.modify(|_, w|
-                w.latency().bits(((sysclk - 1) / flash_latency_step) as u8)
-                .prften().set_bit()
-                .icen().set_bit()
-                .dcen().set_bit()
-            )
+            // PLLI2SON, wait for PLLI2SRDY to clear
+            rcc.cr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << 26)) });
+            wait_ready(Clock::Plli2s, || rcc.cr.read().bits() & 1 << 27 == 0)?;
         }
+        Ok(())
     }
 
-    pub fn freeze(self) -> Clocks {
-        let rcc = unsafe { &*RCC::ptr() };
-
-        let (use_pll, sysclk) = self.pll_setup();
-
+    /// Starts the PLLI2S
+    #[allow(unused_variables)]
+    fn plli2s_setup(pll: &AltPll) -> Result<(), ClockError> {
         #[cfg(any(
             feature = "stm32f401",
             feature = "stm32f405",
             feature = "stm32f407",
-            feature = "stm32f410",
             feature = "stm32f411",
             feature = "stm32f412",
             feature = "stm32f413",
@@ -192,21 +1166,399 @@ impl CFGR {
             feature = "stm32f429",
             feature = "stm32f437",
             feature = "stm32f439",
+            feature = "stm32f446",
             feature = "stm32f469",
             feature = "stm32f479"
         ))]
-        let sysclk_min = 24_000_000;
+        {
+            let rcc = unsafe { &*RCC::ptr() };
 
-        #[cfg(any(feature = "stm32f446"))]
-        let sysclk_min = 12_500_000;
+            // the configuration can only be changed while the PLLI2S is off
+            Self::plli2s_disable()?;
 
-        #[cfg(feature = "stm32f401")]
-        let sysclk_max = 84_000_000;
+            // PLLI2SN
+            let mut mask = 0x1ff << 6;
+            let mut bits = pll.n << 6;
+            #[cfg(any(
+                feature = "stm32f411",
+                feature = "stm32f412",
+                feature = "stm32f413",
+                feature = "stm32f423",
+                feature = "stm32f446"
+            ))]
+            {
+                // PLLI2SM
+                mask |= 0x3f;
+                bits |= pll.m;
+            }
+            #[cfg(any(
+                feature = "stm32f412",
+                feature = "stm32f413",
+                feature = "stm32f423"
+            ))]
+            {
+                // PLLI2SSRC: same source as the main PLL
+                mask |= 1 << 22;
+            }
+            if let Some(q) = pll.q {
+                mask |= 0xf << 24;
+                bits |= q << 24;
+            }
+            if let Some(r) = pll.r {
+                mask |= 0x7 << 28;
+                bits |= r << 28;
+            }
+            rcc.plli2scfgr.modify(|r, w| unsafe { w.bits(r.bits() & !mask | bits) });
+
+            // PLLI2SON, wait for PLLI2SRDY
+            rcc.cr.modify(|r, w| unsafe { w.bits(r.bits() | 1 << 26) });
+            if let Err(e) = wait_ready(Clock::Plli2s, || rcc.cr.read().bits() & 1 << 27 != 0) {
+                rcc.cr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << 26)) });
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
 
+    /// Stops the PLLSAI
+    fn pllsai_disable() -> Result<(), ClockError> {
         #[cfg(any(
-            feature = "stm32f405",
-            feature = "stm32f407",
-            feature = "stm32f415",
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        {
+            let rcc = unsafe { &*RCC::ptr() };
+
+            // PLLSAION, wait for PLLSAIRDY to clear
+            rcc.cr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << 28)) });
+            wait_ready(Clock::Pllsai, || rcc.cr.read().bits() & 1 << 29 == 0)?;
+        }
+        Ok(())
+    }
+
+    /// Starts the PLLSAI
+    #[allow(unused_variables)]
+    fn pllsai_setup(pll: &AltPll) -> Result<(), ClockError> {
+        #[cfg(any(
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        {
+            let rcc = unsafe { &*RCC::ptr() };
+
+            // the configuration can only be changed while the PLLSAI is off
+            Self::pllsai_disable()?;
+
+            // PLLSAIN
+            let mut mask = 0x1ff << 6;
+            let mut bits = pll.n << 6;
+            #[cfg(feature = "stm32f446")]
+            {
+                // PLLSAIM
+                mask |= 0x3f;
+                bits |= pll.m;
+            }
+            if let Some(p) = pll.p {
+                mask |= 0x3 << 16;
+                bits |= (p / 2 - 1) << 16;
+            }
+            if let Some(q) = pll.q {
+                mask |= 0xf << 24;
+                bits |= q << 24;
+            }
+            rcc.pllsaicfgr.modify(|r, w| unsafe { w.bits(r.bits() & !mask | bits) });
+
+            if let Some(divq) = pll.divq {
+                // PLLSAIDIVQ, SAI clock sources = PLLSAI
+                rcc.dckcfgr.modify(|r, w| unsafe {
+                    w.bits(r.bits() & !(0xf << 20 | 0x1f << 8) | (divq - 1) << 8)
+                });
+            }
+
+            // PLLSAION, wait for PLLSAIRDY
+            rcc.cr.modify(|r, w| unsafe { w.bits(r.bits() | 1 << 28) });
+            if let Err(e) = wait_ready(Clock::Pllsai, || rcc.cr.read().bits() & 1 << 29 != 0) {
+                rcc.cr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << 28)) });
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts the LSE, unless it is already running in the requested mode
+    ///
+    /// The backup domain isn't reset by a system reset, so the LSE keeps running and doesn't
+    /// need to be restarted.
+    fn lse_setup(&self) -> Result<(), ClockError> {
+        let rcc = unsafe { &*RCC::ptr() };
+
+        // NOTE(unsafe) the PWR peripheral is only used to enable backup domain access
+        backup_domain_access(unsafe { &*PWR::ptr() })?;
+
+        let bdcr = rcc.bdcr.read();
+        // LSEBYP
+        let (mode, mask) = (u32::from(self.lse_bypass) << 2, 1 << 2);
+        // LSEMOD
+        #[cfg(any(
+            feature = "stm32f401",
+            feature = "stm32f410",
+            feature = "stm32f411",
+            feature = "stm32f412",
+            feature = "stm32f413",
+            feature = "stm32f423",
+            feature = "stm32f446"
+        ))]
+        let (mode, mask) = match self.lse_drive {
+            Some(drive) => (mode | u32::from(drive == LseDrive::High) << 3, mask | 1 << 3),
+            None => (mode, mask),
+        };
+        if bdcr.lserdy().bit_is_set() && bdcr.bits() & mask == mode {
+            return Ok(());
+        }
+
+        // LSEBYP and LSEMOD can only be written while the LSE is off
+        rcc.bdcr.modify(|_, w| w.lseon().clear_bit());
+        wait_ready(Clock::Lse, || rcc.bdcr.read().lserdy().bit_is_clear())?;
+        rcc.bdcr.modify(|r, w| unsafe { w.bits(r.bits() & !mask | mode) });
+
+        // enable LSE and wait for it to be ready
+        rcc.bdcr.modify(|_, w| w.lseon().set_bit());
+        if let Err(e) = wait_ready(Clock::Lse, || rcc.bdcr.read().lserdy().bit_is_set()) {
+            rcc.bdcr.modify(|_, w| w.lseon().clear_bit());
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Returns the flash wait states needed for the CPU clock `hclk` at the configured supply
+    /// voltage
+    fn flash_latency(&self, hclk: u32) -> Result<u32, ClockError> {
+        #[cfg(any(
+            feature = "stm32f401",
+            feature = "stm32f410",
+            feature = "stm32f411",
+            feature = "stm32f412"
+        ))]
+        let flash_latency_step = match self.voltage {
+            SupplyVoltage::V1_8To2_1 => 16_000_000,
+            SupplyVoltage::V2_1To2_4 => 18_000_000,
+            SupplyVoltage::V2_4To2_7 => 24_000_000,
+            SupplyVoltage::V2_7To3_6 => 30_000_000,
+        };
+
+        #[cfg(any(
+            feature = "stm32f405",
+            feature = "stm32f407",
+            feature = "stm32f415",
+            feature = "stm32f417",
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        let flash_latency_step = match self.voltage {
+            SupplyVoltage::V1_8To2_1 => 20_000_000,
+            SupplyVoltage::V2_1To2_4 => 22_000_000,
+            SupplyVoltage::V2_4To2_7 => 24_000_000,
+            SupplyVoltage::V2_7To3_6 => 30_000_000,
+        };
+
+        #[cfg(any(
+            feature = "stm32f413",
+            feature = "stm32f423"
+        ))]
+        let flash_latency_step = match self.voltage {
+            SupplyVoltage::V1_8To2_1 => 16_000_000,
+            SupplyVoltage::V2_1To2_4 => 18_000_000,
+            SupplyVoltage::V2_4To2_7 => 20_000_000,
+            SupplyVoltage::V2_7To3_6 => 25_000_000,
+        };
+
+        // LATENCY is 3 bits wide on the F40x/F41x and 4 bits on the others
+        #[cfg(any(
+            feature = "stm32f405",
+            feature = "stm32f407",
+            feature = "stm32f415",
+            feature = "stm32f417"
+        ))]
+        let flash_latency_max = 7;
+        #[cfg(any(
+            feature = "stm32f401",
+            feature = "stm32f410",
+            feature = "stm32f411",
+            feature = "stm32f412",
+            feature = "stm32f413",
+            feature = "stm32f423",
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        let flash_latency_max = 15;
+
+        let flash_latency = (hclk - 1) / flash_latency_step;
+        if flash_latency > flash_latency_max {
+            return Err(ClockError::OutOfRange {
+                clock: Clock::Hclk,
+                requested: hclk,
+                min: 0,
+                max: (flash_latency_max + 1) * flash_latency_step,
+            });
+        }
+        Ok(flash_latency)
+    }
+
+    fn flash_setup(&self, flash_latency: u32) {
+        // The prefetch buffer must be off below 2.1 V
+        let prefetch = self.voltage != SupplyVoltage::V1_8To2_1;
+
+        unsafe {
+            let flash = &(*FLASH::ptr());
+            // Adjust flash wait states
+            flash.acr.modify(|_, w|
+                w.latency().bits(flash_latency as u8)
+                .prften().bit(prefetch)
+                .icen().set_bit()
+                .dcen().set_bit()
+            )
+        }
+    }
+
+    /// Selects the lowest regulator voltage scale that supports `sysclk`
+    ///
+    /// The new scale only takes effect once the main PLL is enabled.
+    fn voltage_scaling_setup(sysclk: u32) {
+        let rcc = unsafe { &*RCC::ptr() };
+        // NOTE(unsafe) only the VOS bits of the PWR peripheral are modified
+        let pwr = unsafe { &*PWR::ptr() };
+
+        let (mask, vos) = voltage_scale(sysclk);
+
+        rcc.apb1enr.modify(|_, w| w.pwren().set_bit());
+        pwr.cr.modify(|r, w| unsafe { w.bits(r.bits() & !mask | vos) });
+    }
+
+    /// Enables the over-drive mode needed above 168 MHz, while the main PLL is running but not
+    /// yet selected as system clock
+    fn over_drive_setup() -> Result<(), ClockError> {
+        #[cfg(any(
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        {
+            // NOTE(unsafe) only the over-drive bits of the PWR peripheral are modified
+            let pwr = unsafe { &*PWR::ptr() };
+
+            // ODEN, wait for ODRDY
+            pwr.cr.modify(|r, w| unsafe { w.bits(r.bits() | 1 << 16) });
+            wait_ready(Clock::OverDrive, || pwr.csr.read().bits() & 1 << 16 != 0)?;
+
+            // ODSWEN, wait for ODSWRDY
+            pwr.cr.modify(|r, w| unsafe { w.bits(r.bits() | 1 << 17) });
+            wait_ready(Clock::OverDrive, || pwr.csr.read().bits() & 1 << 17 != 0)?;
+        }
+        Ok(())
+    }
+
+    /// Disables the over-drive mode, while the system runs from HSI
+    fn over_drive_disable() -> Result<(), ClockError> {
+        #[cfg(any(
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        {
+            // NOTE(unsafe) only the over-drive bits of the PWR peripheral are modified
+            let pwr = unsafe { &*PWR::ptr() };
+
+            if pwr.cr.read().bits() & 1 << 16 != 0 {
+                // ODEN and ODSWEN, wait for ODSWRDY to clear
+                pwr.cr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << 16 | 1 << 17)) });
+                wait_ready(Clock::OverDrive, || pwr.csr.read().bits() & 1 << 17 == 0)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Switches the system clock to HSI, so the HSE and the main PLL can be reconfigured
+    fn switch_to_hsi() -> Result<(), ClockError> {
+        let rcc = unsafe { &*RCC::ptr() };
+
+        rcc.cr.modify(|_, w| w.hsion().set_bit());
+        wait_ready(Clock::Hsi, || rcc.cr.read().hsirdy().bit_is_set())?;
+
+        rcc.cfgr.modify(|_, w| w.sw().variant(SW_A::HSI));
+        // wait for SWS to follow
+        wait_ready(Clock::Sysclk, || rcc.cfgr.read().bits() >> 2 & 0b11 == 0b00)
+    }
+
+    /// Applies the clock configuration
+    ///
+    /// Panics if the configuration can't be achieved, see `try_freeze`.
+    pub fn freeze(self) -> Clocks {
+        self.try_freeze().unwrap()
+    }
+
+    /// Computes the dividers and frequencies of this configuration without touching the hardware
+    ///
+    /// The result depends only on the configuration and the selected device, so it can be
+    /// checked on the host. `try_freeze` applies the same plan.
+    pub fn plan(&self) -> Result<ClockPlan, ClockError> {
+        #[cfg(any(
+            feature = "stm32f401",
+            feature = "stm32f405",
+            feature = "stm32f407",
+            feature = "stm32f410",
+            feature = "stm32f411",
+            feature = "stm32f412",
+            feature = "stm32f413",
+            feature = "stm32f415",
+            feature = "stm32f417",
+            feature = "stm32f423",
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        let sysclk_min = 24_000_000;
+
+        #[cfg(any(feature = "stm32f446"))]
+        let sysclk_min = 12_500_000;
+
+        #[cfg(feature = "stm32f401")]
+        let sysclk_max = 84_000_000;
+
+        #[cfg(any(
+            feature = "stm32f405",
+            feature = "stm32f407",
+            feature = "stm32f415",
             feature = "stm32f417"
         ))]
         let sysclk_max = 168_000_000;
@@ -231,9 +1583,72 @@ impl CFGR {
         ))]
         let sysclk_max = 180_000_000;
 
-        assert!(!use_pll || sysclk <= sysclk_max && sysclk >= sysclk_min);
+        #[cfg(any(
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        // Over-drive, needed above 168 MHz, requires at least 2.1 V
+        let sysclk_max = if self.voltage == SupplyVoltage::V1_8To2_1 {
+            168_000_000
+        } else {
+            sysclk_max
+        };
+
+        if let Some(requested) = self.sysclk {
+            let use_pll = requested != self.hse.unwrap_or(HSI);
+            if use_pll && (requested < sysclk_min || requested > sysclk_max) {
+                return Err(ClockError::OutOfRange {
+                    clock: Clock::Sysclk,
+                    requested,
+                    min: sysclk_min,
+                    max: sysclk_max,
+                });
+            }
+        }
 
+        if let Some(requested) = self.lse {
+            if requested > 1_000_000 {
+                return Err(ClockError::OutOfRange {
+                    clock: Clock::Lse,
+                    requested,
+                    min: 0,
+                    max: 1_000_000,
+                });
+            }
+        }
+
+        // The PLLI2S and PLLSAI outputs divide the VCO of at most 432 MHz by at least 2
+        for &(clock, requested) in &[(Clock::I2sclk, self.i2sclk), (Clock::Saiclk, self.saiclk)] {
+            match requested {
+                Some(requested) if requested == 0 || requested > 216_000_000 => {
+                    return Err(ClockError::OutOfRange {
+                        clock,
+                        requested,
+                        min: 1,
+                        max: 216_000_000,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let pll = self.pll_setup()?;
+        let sysclk = pll.sysclk;
+        self.check_exact(Clock::Sysclk, self.sysclk, sysclk)?;
         let hclk = self.hclk.unwrap_or(sysclk);
+        if hclk == 0 {
+            return Err(ClockError::OutOfRange {
+                clock: Clock::Hclk,
+                requested: 0,
+                min: 1,
+                max: sysclk,
+            });
+        }
         let (hpre_bits, hpre_div) = match (sysclk + hclk - 1) / hclk {
             0 => unreachable!(),
             1 => (HPRE_A::DIV1, 1),
@@ -249,6 +1664,8 @@ impl CFGR {
 
         // Calculate real AHB clock
         let hclk = sysclk / hpre_div;
+        self.check_exact(Clock::Hclk, self.hclk, hclk)?;
+        let flash_latency = self.flash_latency(hclk)?;
 
         #[cfg(any(
             feature = "stm32f401",
@@ -278,6 +1695,14 @@ impl CFGR {
         let (pclk1_max, pclk2_max) = (50_000_000, 100_000_000);
 
         let pclk1 = self.pclk1.unwrap_or_else(|| core::cmp::min(pclk1_max, hclk));
+        if pclk1 == 0 {
+            return Err(ClockError::OutOfRange {
+                clock: Clock::Pclk1,
+                requested: 0,
+                min: 1,
+                max: pclk1_max,
+            });
+        }
         let (ppre1_bits, ppre1) = match (hclk + pclk1 - 1) / pclk1 {
             0 => unreachable!(),
             1 => (0b000, 1),
@@ -290,9 +1715,25 @@ impl CFGR {
         // Calculate real APB1 clock
         let pclk1 = hclk / u32::from(ppre1);
 
-        assert!(pclk1 <= pclk1_max);
+        if pclk1 > pclk1_max {
+            return Err(ClockError::OutOfRange {
+                clock: Clock::Pclk1,
+                requested: self.pclk1.unwrap_or(pclk1),
+                min: 0,
+                max: pclk1_max,
+            });
+        }
+        self.check_exact(Clock::Pclk1, self.pclk1, pclk1)?;
 
         let pclk2 = self.pclk2.unwrap_or_else(|| core::cmp::min(pclk2_max, hclk));
+        if pclk2 == 0 {
+            return Err(ClockError::OutOfRange {
+                clock: Clock::Pclk2,
+                requested: 0,
+                min: 1,
+                max: pclk2_max,
+            });
+        }
         let (ppre2_bits, ppre2) = match (hclk + pclk2 - 1) / pclk2 {
             0 => unreachable!(),
             1 => (0b000, 1),
@@ -305,24 +1746,326 @@ impl CFGR {
         // Calculate real APB2 clock
         let pclk2 = hclk / u32::from(ppre2);
 
-        assert!(pclk2 <= pclk2_max);
+        if pclk2 > pclk2_max {
+            return Err(ClockError::OutOfRange {
+                clock: Clock::Pclk2,
+                requested: self.pclk2.unwrap_or(pclk2),
+                min: 0,
+                max: pclk2_max,
+            });
+        }
+        self.check_exact(Clock::Pclk2, self.pclk2, pclk2)?;
 
-        Self::flash_setup(sysclk);
+        let timclk1 = timer_clock(hclk, pclk1, ppre1, self.timpre);
+        let timclk2 = timer_clock(hclk, pclk2, ppre2, self.timpre);
+
+        Ok(ClockPlan {
+            sysclk,
+            hclk,
+            pclk1,
+            pclk2,
+            timclk1,
+            timclk2,
+            hpre_bits,
+            ppre1_bits,
+            ppre2_bits,
+            ppre1,
+            ppre2,
+            flash_latency,
+            pll,
+        })
+    }
+
+    /// Applies the clock configuration, or returns which constraint could not be met without
+    /// touching the clock configuration
+    ///
+    /// If an oscillator, PLL or the over-drive mode doesn't start or stop, `ClockError::Timeout`
+    /// is returned. The system then runs from HSI with the previous AHB and APB prescalers, the
+    /// main PLL, the over-drive mode and the HSE are off, unless the HSE still feeds the PLLI2S
+    /// or PLLSAI, and the flash latency and voltage scaling are set for HSI.
+    ///
+    /// The clocks can be reconfigured at runtime by freezing another `CFGR`, e.g. a clone of the
+    /// one from `Rcc`. A running PLLI2S or PLLSAI is stopped if its source or input divider
+    /// changes and only restarted if the new configuration sets its clock. Drivers created with
+    /// the previous `Clocks` keep their old timings and have to be reconfigured with the new
+    /// ones, with `set_baudrate` of `Serial`, `Tx`, `Rs485` and `BufferedSerial`, or with
+    /// `reconfigure` of `I2c`, `Spi`, `Timer`, `Pwm`, `MonoTimer`, `Capture` and `PwmInput`.
+    pub fn try_freeze(self) -> Result<Clocks, ClockError> {
+        let plan = self.plan()?;
+        self.apply(&plan)
+    }
+
+    /// Writes a plan computed by `plan` to the hardware, falling back to HSI if it fails
+    fn apply(&self, plan: &ClockPlan) -> Result<Clocks, ClockError> {
+        self.apply_plan(plan).map_err(|e| {
+            self.fall_back_to_hsi();
+            e
+        })
+    }
+
+    /// Leaves the system running from HSI after `apply_plan` failed
+    fn fall_back_to_hsi(&self) {
+        let rcc = unsafe { &*RCC::ptr() };
+
+        if Self::switch_to_hsi().is_err() {
+            // The main PLL may still be the system clock, leave everything as it is
+            return;
+        }
+        rcc.cr.modify(|_, w| w.pllon().clear_bit());
+        // Nothing else can be done if the over-drive mode doesn't switch off, 16 MHz are
+        // supported either way
+        let _ = Self::over_drive_disable();
+        Self::voltage_scaling_setup(HSI);
+        // HCLK is at most HSI, which needs the fewest wait states at any voltage
+        self.flash_setup(self.flash_latency(HSI).unwrap_or(0));
+
+        if rcc.cr.read().bits() & (1 << 26 | 1 << 28) == 0 {
+            rcc.cr.modify(|_, w| w.csson().clear_bit().hseon().clear_bit());
+        }
+    }
+
+    fn apply_plan(&self, plan: &ClockPlan) -> Result<Clocks, ClockError> {
+        let rcc = unsafe { &*RCC::ptr() };
+        let ClockPlan {
+            sysclk,
+            hclk,
+            pclk1,
+            pclk2,
+            timclk1,
+            timclk2,
+            hpre_bits,
+            ppre1_bits,
+            ppre2_bits,
+            ppre1,
+            ppre2,
+            flash_latency,
+            ref pll,
+        } = *plan;
+
+        // When the clocks are reconfigured at runtime, wait states have to be added before the
+        // system clock gets faster, and may only be removed once it got slower
+        let flash = unsafe { &*FLASH::ptr() };
+        let flash_latency_up = flash_latency >= u32::from(flash.acr.read().latency().bits());
+        if flash_latency_up {
+            self.flash_setup(flash_latency);
+        }
+
+        // Run from HSI while the HSE and the main PLL are (re)configured, this is a no-op after
+        // reset
+        Self::switch_to_hsi()?;
+        if rcc.cr.read().pllon().bit_is_set() {
+            rcc.cr.modify(|_, w| w.pllon().clear_bit());
+            wait_ready(Clock::Pll, || rcc.cr.read().pllrdy().bit_is_clear())?;
+        }
+        if sysclk <= 168_000_000 {
+            Self::over_drive_disable()?;
+        }
+
+        // The PLLI2S and PLLSAI share the source and, depending on the device, the input divider
+        // of the main PLL. Stop them before their input changes, so they don't keep feeding the
+        // I2S and SAI peripherals with a different frequency. They are restarted below if this
+        // configuration uses them.
+        let pllcfgr = rcc.pllcfgr.read().bits();
+        let pllsrc_hse = pllcfgr & 1 << 22 != 0;
+        let cr = rcc.cr.read();
+        let hse_restart = pllsrc_hse
+            && self.hse.is_some()
+            && (cr.hserdy().bit_is_clear() || cr.hsebyp().bit() != self.hse_bypass);
+        let (pllsrc_change, pllm_change) = match pll.main.map(|main| main.m).or(pll.pllm) {
+            Some(m) => (pllsrc_hse != self.hse.is_some(), pllcfgr & 0x3f != m),
+            None => (false, false),
+        };
+        if hse_restart || pllsrc_change || (pllm_change && !PLLI2S_OWN_M) {
+            Self::plli2s_disable()?;
+        }
+        if hse_restart || pllsrc_change || (pllm_change && !PLLSAI_OWN_M) {
+            Self::pllsai_disable()?;
+        }
+
+        // VOS can only be changed while the main PLL is off
+        Self::voltage_scaling_setup(sysclk);
 
         if self.hse.is_some() {
-            // enable HSE and wait for it to be ready
-            rcc.cr.modify(|_, w| w.hseon().set_bit());
-            while rcc.cr.read().hserdy().bit_is_clear() {}
+            let cr = rcc.cr.read();
+            if cr.hserdy().bit_is_clear() || cr.hsebyp().bit() != self.hse_bypass {
+                // HSEBYP can only be written while the HSE is off
+                rcc.cr.modify(|_, w| w.csson().clear_bit().hseon().clear_bit());
+                wait_ready(Clock::Hse, || rcc.cr.read().hserdy().bit_is_clear())?;
+                rcc.cr.modify(|_, w| w.hsebyp().bit(self.hse_bypass));
+
+                // enable HSE and wait for it to be ready
+                rcc.cr.modify(|_, w| w.hseon().set_bit());
+                if let Err(e) = wait_ready(Clock::Hse, || rcc.cr.read().hserdy().bit_is_set()) {
+                    rcc.cr.modify(|_, w| w.hseon().clear_bit());
+                    return Err(e);
+                }
+            }
+
+            rcc.cr.modify(|_, w| w.csson().bit(self.css));
         }
 
-        if use_pll {
+        if let Some(main) = pll.main {
+            rcc.pllcfgr.write(|w| unsafe {
+                w.pllm()
+                    .bits(main.m as u8)
+                    .plln()
+                    .bits(main.n as u16)
+                    .pllp()
+                    .bits((main.p / 2 - 1) as u8)
+                    .pllq()
+                    .bits(main.q as u8)
+                    .pllsrc()
+                    .bit(self.hse.is_some())
+            });
+
             // Enable PLL
             rcc.cr.modify(|_, w| w.pllon().set_bit());
 
             // Wait for PLL to stabilise
-            while rcc.cr.read().pllrdy().bit_is_clear() {}
+            if let Err(e) = wait_ready(Clock::Pll, || rcc.cr.read().pllrdy().bit_is_set()) {
+                rcc.cr.modify(|_, w| w.pllon().clear_bit());
+                return Err(e);
+            }
+
+            if sysclk > 168_000_000 {
+                Self::over_drive_setup()?;
+            }
+        } else if let Some(pllm) = pll.pllm {
+            // The PLLI2S and PLLSAI use the source and, depending on the device, the input
+            // divider of the main PLL
+            rcc.pllcfgr.write(|w| unsafe {
+                w.pllm()
+                    .bits(pllm as u8)
+                    .pllsrc()
+                    .bit(self.hse.is_some())
+            });
+        }
+
+        if let Some(plli2s) = &pll.plli2s {
+            Self::plli2s_setup(plli2s)?;
+        }
+
+        #[cfg(any(
+            feature = "stm32f401",
+            feature = "stm32f405",
+            feature = "stm32f407",
+            feature = "stm32f411",
+            feature = "stm32f412",
+            feature = "stm32f413",
+            feature = "stm32f415",
+            feature = "stm32f417",
+            feature = "stm32f423",
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        {
+            // I2S clock source: PLLI2S or I2S_CKIN
+            let ckin = self.i2s_ckin.is_some();
+            #[cfg(any(
+                feature = "stm32f401",
+                feature = "stm32f405",
+                feature = "stm32f407",
+                feature = "stm32f411",
+                feature = "stm32f415",
+                feature = "stm32f417",
+                feature = "stm32f427",
+                feature = "stm32f429",
+                feature = "stm32f437",
+                feature = "stm32f439",
+                feature = "stm32f469",
+                feature = "stm32f479"
+            ))]
+            rcc.cfgr.modify(|_, w| w.i2ssrc().bit(ckin));
+            #[cfg(any(
+                feature = "stm32f412",
+                feature = "stm32f413",
+                feature = "stm32f423",
+                feature = "stm32f446"
+            ))]
+            rcc.dckcfgr.modify(|r, w| unsafe {
+                // I2S1SRC and I2S2SRC
+                let src = if ckin { 0b01 << 27 | 0b01 << 25 } else { 0 };
+                w.bits(r.bits() & !(0b1111 << 25) | src)
+            });
+        }
+
+        if let Some(pllsai) = &pll.pllsai {
+            Self::pllsai_setup(pllsai)?;
+        }
+
+        // PLL48CLK from the PLLI2S or PLLSAI instead of the main PLL
+        #[cfg(any(
+            feature = "stm32f412",
+            feature = "stm32f413",
+            feature = "stm32f423",
+            feature = "stm32f446"
+        ))]
+        rcc.dckcfgr2.modify(|_, w| w.ck48msel().bit(pll.pll48_alt));
+        #[cfg(any(
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        rcc.dckcfgr.modify(|_, w| w.ck48msel().bit(pll.pll48_alt));
+
+        if self.lsi {
+            // enable LSI and wait for it to be ready
+            rcc.csr.modify(|_, w| w.lsion().set_bit());
+            wait_ready(Clock::Lsi, || rcc.csr.read().lsirdy().bit_is_set())?;
+        }
+
+        if self.lse.is_some() {
+            self.lse_setup()?;
         }
 
+        if let Some((source, prescaler)) = self.mco1 {
+            // MCO1 and MCO1PRE
+            rcc.cfgr.modify(|r, w| unsafe {
+                w.bits(
+                    r.bits() & !(0b11 << 21 | 0b111 << 24)
+                        | (source as u32) << 21
+                        | (prescaler as u32) << 24,
+                )
+            });
+        }
+
+        if let Some((source, prescaler)) = self.mco2 {
+            // MCO2PRE and MCO2
+            rcc.cfgr.modify(|r, w| unsafe {
+                w.bits(
+                    r.bits() & !(0b111 << 27 | 0b11 << 30)
+                        | (prescaler as u32) << 27
+                        | (source as u32) << 30,
+                )
+            });
+        }
+
+        // TIMPRE
+        #[cfg(any(
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        rcc.dckcfgr.modify(|r, w| unsafe {
+            w.bits(r.bits() & !(1 << 24) | u32::from(self.timpre) << 24)
+        });
+
+        let sw = if pll.use_pll {
+            SW_A::PLL
+        } else if self.hse.is_some() {
+            SW_A::HSE
+        } else {
+            SW_A::HSI
+        };
+
         // Set scaling factors and select system clock source
         rcc.cfgr.modify(|_, w| unsafe {
             w.ppre2()
@@ -332,29 +2075,235 @@ impl CFGR {
                 .hpre()
                 .variant(hpre_bits)
                 .sw()
-                .variant(if use_pll {
-                    SW_A::PLL
-                } else if self.hse.is_some() {
-                    SW_A::HSE
-                } else {
-                    SW_A::HSI
-                })
+                .variant(sw)
         });
+        // wait for SWS to follow
+        wait_ready(Clock::Sysclk, || rcc.cfgr.read().bits() >> 2 & 0b11 == sw as u32)?;
+
+        if !flash_latency_up {
+            self.flash_setup(flash_latency);
+        }
+
+        // Stop the HSE if neither the system clock nor the PLLI2S or PLLSAI use it any more
+        if self.hse.is_none() && rcc.cr.read().bits() & (1 << 26 | 1 << 28) == 0 {
+            rcc.cr.modify(|_, w| w.csson().clear_bit().hseon().clear_bit());
+        }
 
-        Clocks {
+        Ok(Clocks {
             hclk: Hertz(hclk),
             pclk1: Hertz(pclk1),
             pclk2: Hertz(pclk2),
             ppre1,
             ppre2,
+            timclk1: Hertz(timclk1),
+            timclk2: Hertz(timclk2),
             sysclk: Hertz(sysclk),
+            pll48clk: pll.pll48clk.map(Hertz),
+            i2sclk: pll.i2sclk.map(Hertz),
+            i2sclk_error_ppm: self.i2sclk.and_then(|req| pll.i2sclk.map(|i2s| error_ppm(req, i2s))),
+            saiclk: pll.saiclk.map(Hertz),
+            saiclk_error_ppm: self.saiclk.and_then(|req| pll.saiclk.map(|sai| error_ppm(req, sai))),
+            lse: self.lse.map(Hertz),
+            lsi: if self.lsi { Some(Hertz(LSI)) } else { None },
+        })
+    }
+}
+
+/// Dividers and resulting frequencies of a clock configuration, see `CFGR::plan`
+#[derive(Clone, Copy, Debug)]
+pub struct ClockPlan {
+    sysclk: u32,
+    hclk: u32,
+    pclk1: u32,
+    pclk2: u32,
+    timclk1: u32,
+    timclk2: u32,
+    hpre_bits: HPRE_A,
+    ppre1_bits: u8,
+    ppre2_bits: u8,
+    ppre1: u8,
+    ppre2: u8,
+    flash_latency: u32,
+    pll: PllSetup,
+}
+
+impl ClockPlan {
+    /// Returns the system (core) frequency
+    pub fn sysclk(&self) -> Hertz {
+        Hertz(self.sysclk)
+    }
+
+    /// Returns the frequency of the AHB1
+    pub fn hclk(&self) -> Hertz {
+        Hertz(self.hclk)
+    }
+
+    /// Returns the frequency of the APB1
+    pub fn pclk1(&self) -> Hertz {
+        Hertz(self.pclk1)
+    }
+
+    /// Returns the frequency of the APB2
+    pub fn pclk2(&self) -> Hertz {
+        Hertz(self.pclk2)
+    }
+
+    /// Returns the prescaler of the APB1
+    pub fn ppre1(&self) -> u8 {
+        self.ppre1
+    }
+
+    /// Returns the prescaler of the APB2
+    pub fn ppre2(&self) -> u8 {
+        self.ppre2
+    }
+
+    /// Returns the clock of the timers on the APB1
+    pub fn timclk1(&self) -> Hertz {
+        Hertz(self.timclk1)
+    }
+
+    /// Returns the clock of the timers on the APB2
+    pub fn timclk2(&self) -> Hertz {
+        Hertz(self.timclk2)
+    }
+
+    /// Returns the frequency of the PLL48CLK clock
+    pub fn pll48clk(&self) -> Option<Hertz> {
+        self.pll.pll48clk.map(Hertz)
+    }
+
+    /// Returns the frequency of the I2S clock
+    pub fn i2sclk(&self) -> Option<Hertz> {
+        self.pll.i2sclk.map(Hertz)
+    }
+
+    /// Returns the frequency of the SAI clock
+    pub fn saiclk(&self) -> Option<Hertz> {
+        self.pll.saiclk.map(Hertz)
+    }
+
+    /// Returns the dividers of the main PLL, if it is used
+    pub fn main_pll(&self) -> Option<PllConfig> {
+        self.pll.main
+    }
+
+    /// Returns whether the system clock is taken from the main PLL
+    pub fn uses_pll(&self) -> bool {
+        self.pll.use_pll
+    }
+
+    /// Returns the number of flash wait states
+    pub fn flash_latency(&self) -> u32 {
+        self.flash_latency
+    }
+}
+
+/// Cause of the last reset, read from the reset flags in RCC_CSR
+///
+/// The flags are kept until cleared, so they should be read early in `main` and cleared with
+/// `ResetReason::clear` to tell the next reset apart.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResetReason {
+    /// Entering standby or stop mode was prevented by an option byte
+    LowPower,
+    /// The window watchdog (WWDG) expired
+    WindowWatchdog,
+    /// The independent watchdog (IWDG) expired
+    IndependentWatchdog,
+    /// Software reset through `SCB::sys_reset`
+    Software,
+    /// Power-on or power-down reset
+    PowerOn,
+    /// The supply voltage dropped below the brown-out threshold
+    BrownOut,
+    /// The NRST pin was pulled low
+    Pin,
+    /// No reset flag is set, e.g. because they were cleared
+    Unknown,
+}
+
+impl ResetReason {
+    /// Returns the cause of the last reset without clearing the flags
+    ///
+    /// A power-on reset also sets the brown-out and pin flags, and a brown-out reset the pin
+    /// flag, so the most specific flag wins.
+    pub fn read() -> Self {
+        // NOTE(unsafe) atomic read with no side effects
+        let csr = unsafe { (*RCC::ptr()).csr.read().bits() };
+
+        if csr & 1 << 29 != 0 {
+            ResetReason::IndependentWatchdog
+        } else if csr & 1 << 30 != 0 {
+            ResetReason::WindowWatchdog
+        } else if csr & 1 << 31 != 0 {
+            ResetReason::LowPower
+        } else if csr & 1 << 28 != 0 {
+            ResetReason::Software
+        } else if csr & 1 << 27 != 0 {
+            ResetReason::PowerOn
+        } else if csr & 1 << 25 != 0 {
+            ResetReason::BrownOut
+        } else if csr & 1 << 26 != 0 {
+            ResetReason::Pin
+        } else {
+            ResetReason::Unknown
         }
     }
+
+    /// Clears the reset flags
+    pub fn clear() {
+        // NOTE(unsafe) RMVF is write-only and the other bits are preserved
+        let rcc = unsafe { &*RCC::ptr() };
+        rcc.csr.modify(|r, w| unsafe { w.bits(r.bits() | 1 << 24) });
+    }
+
+    /// Returns the cause of the last reset and clears the flags
+    pub fn read_and_clear() -> Self {
+        let reason = Self::read();
+        Self::clear();
+        reason
+    }
+}
+
+/// Handles a Clock Security System failure, call this from the `NMI` exception handler
+///
+/// When the HSE fails, the hardware stops the HSE and the main PLL and switches the system clock
+/// to HSI, so the frequencies in `Clocks` are no longer valid. Returns `true` if the NMI was
+/// caused by the CSS, after clearing the flag that would otherwise raise the NMI again.
+pub fn handle_css_nmi() -> bool {
+    let rcc = unsafe { &*RCC::ptr() };
+
+    if rcc.cir.read().cssf().bit_is_clear() {
+        return false;
+    }
+    rcc.cir.modify(|_, w| w.cssc().set_bit());
+    true
+}
+
+/// Enables write access to the backup domain: the RTC, the backup registers and the LSE
+///
+/// Note that `CFGR::freeze` also modifies PWR registers without owning `PWR`: DBP when the LSE
+/// is used and the voltage scaling and over-drive bits.
+///
+/// Returns `ClockError::Timeout` for the LSE if the write protection isn't disabled.
+pub fn enable_backup_domain_access(pwr: &mut PWR) -> Result<(), ClockError> {
+    backup_domain_access(pwr)
+}
+
+fn backup_domain_access(pwr: &PWR) -> Result<(), ClockError> {
+    let rcc = unsafe { &*RCC::ptr() };
+
+    // enable the PWR clock and set DBP
+    rcc.apb1enr.modify(|_, w| w.pwren().set_bit());
+    pwr.cr.modify(|_, w| w.dbp().set_bit());
+    // wait for the write protection to be disabled
+    wait_ready(Clock::Lse, || pwr.cr.read().dbp().bit_is_set())
 }
 
 /// Frozen clock frequencies
 ///
-/// The existence of this value indicates that the clock configuration can no longer be changed
+/// The frequencies are valid until the clocks are reconfigured with another `CFGR::freeze`
 #[derive(Clone, Copy)]
 pub struct Clocks {
     hclk: Hertz,
@@ -362,7 +2311,16 @@ pub struct Clocks {
     pclk2: Hertz,
     ppre1: u8,
     ppre2: u8,
+    timclk1: Hertz,
+    timclk2: Hertz,
     sysclk: Hertz,
+    pll48clk: Option<Hertz>,
+    i2sclk: Option<Hertz>,
+    i2sclk_error_ppm: Option<i32>,
+    saiclk: Option<Hertz>,
+    saiclk_error_ppm: Option<i32>,
+    lse: Option<Hertz>,
+    lsi: Option<Hertz>,
 }
 
 impl Clocks {
@@ -391,8 +2349,510 @@ impl Clocks {
         self.ppre2
     }
 
+    /// Returns the clock of the timers on the APB1 (TIM2 - TIM7 and TIM12 - TIM14)
+    pub fn timclk1(&self) -> Hertz {
+        self.timclk1
+    }
+
+    /// Returns the clock of the timers on the APB2 (TIM1 and TIM8 - TIM11)
+    pub fn timclk2(&self) -> Hertz {
+        self.timclk2
+    }
+
     /// Returns the system (core) frequency
     pub fn sysclk(&self) -> Hertz {
         self.sysclk
     }
+
+    /// Returns the frequency of the PLL48CLK clock used by USB OTG FS, SDIO and RNG, if a PLL
+    /// generating it is running
+    pub fn pll48clk(&self) -> Option<Hertz> {
+        self.pll48clk
+    }
+
+    /// Returns the frequency of the I2S clock
+    pub fn i2sclk(&self) -> Option<Hertz> {
+        self.i2sclk
+    }
+
+    /// Returns the deviation of the I2S clock from the frequency set with `CFGR::i2s_clk` in
+    /// parts per million, the sample rate deviates by the same amount
+    pub fn i2sclk_error_ppm(&self) -> Option<i32> {
+        self.i2sclk_error_ppm
+    }
+
+    /// Returns the frequency of the SAI clock
+    pub fn saiclk(&self) -> Option<Hertz> {
+        self.saiclk
+    }
+
+    /// Returns the deviation of the SAI clock from the frequency set with `CFGR::sai_clk` in
+    /// parts per million, the sample rate deviates by the same amount
+    pub fn saiclk_error_ppm(&self) -> Option<i32> {
+        self.saiclk_error_ppm
+    }
+
+    /// Returns the frequency of the LSE, if enabled with `CFGR::use_lse`
+    pub fn lse(&self) -> Option<Hertz> {
+        self.lse
+    }
+
+    /// Returns the nominal frequency of the LSI, if enabled with `CFGR::use_lsi`
+    ///
+    /// The actual frequency varies between 17 and 47 kHz depending on device and temperature.
+    pub fn lsi(&self) -> Option<Hertz> {
+        self.lsi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::U32Ext;
+
+    #[cfg(not(feature = "stm32f446"))]
+    const SYSCLK_MIN: u32 = 24_000_000;
+    #[cfg(feature = "stm32f446")]
+    const SYSCLK_MIN: u32 = 12_500_000;
+
+    #[cfg(feature = "stm32f401")]
+    const SYSCLK_MAX: u32 = 84_000_000;
+    #[cfg(any(
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f415",
+        feature = "stm32f417"
+    ))]
+    const SYSCLK_MAX: u32 = 168_000_000;
+    #[cfg(any(
+        feature = "stm32f410",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f423"
+    ))]
+    const SYSCLK_MAX: u32 = 100_000_000;
+    #[cfg(any(
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    const SYSCLK_MAX: u32 = 180_000_000;
+
+    // Flash wait states at 84 MHz for 2.7 - 3.6 V and 1.8 - 2.1 V
+    #[cfg(any(
+        feature = "stm32f401",
+        feature = "stm32f410",
+        feature = "stm32f411",
+        feature = "stm32f412"
+    ))]
+    const LATENCY_84MHZ: (u32, u32) = (2, 5);
+    #[cfg(any(
+        feature = "stm32f413",
+        feature = "stm32f423"
+    ))]
+    const LATENCY_84MHZ: (u32, u32) = (3, 5);
+    #[cfg(any(
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f415",
+        feature = "stm32f417",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    const LATENCY_84MHZ: (u32, u32) = (2, 4);
+
+    // 84 MHz from an 8 MHz HSE, with PLL48CLK from the main PLL
+    fn hse_84mhz() -> CFGR {
+        CFGR::new()
+            .use_hse(8.mhz())
+            .sysclk(84.mhz())
+            .require_pll48clk()
+            .require_exact()
+    }
+
+    #[test]
+    fn hsi_without_pll() {
+        let plan = CFGR::new().plan().unwrap();
+        assert_eq!(plan.sysclk().0, HSI);
+        assert_eq!(plan.hclk().0, HSI);
+        assert_eq!(plan.pclk1().0, HSI);
+        assert_eq!(plan.pclk2().0, HSI);
+        assert_eq!(plan.timclk1().0, HSI);
+        assert!(!plan.uses_pll());
+        assert_eq!(plan.main_pll(), None);
+        assert_eq!(plan.pll48clk().map(|f| f.0), None);
+        assert_eq!(plan.flash_latency(), 0);
+    }
+
+    #[test]
+    fn hse_without_pll() {
+        let plan = CFGR::new().use_hse(25.mhz()).plan().unwrap();
+        assert_eq!(plan.sysclk().0, 25_000_000);
+        assert!(!plan.uses_pll());
+        assert_eq!(plan.main_pll(), None);
+    }
+
+    #[test]
+    fn hse_with_pll() {
+        let plan = hse_84mhz().plan().unwrap();
+        assert!(plan.uses_pll());
+        assert_eq!(plan.sysclk().0, 84_000_000);
+        assert_eq!(plan.hclk().0, 84_000_000);
+        assert_eq!(plan.pclk1().0, 42_000_000);
+        assert_eq!(plan.ppre1(), 2);
+        assert_eq!(plan.timclk1().0, 84_000_000);
+        assert_eq!(plan.pclk2().0, 84_000_000);
+        assert_eq!(plan.pll48clk().map(|f| f.0), Some(48_000_000));
+
+        let pll = plan.main_pll().unwrap();
+        let vco = 8_000_000 / pll.m * pll.n;
+        assert_eq!(vco / pll.p, 84_000_000);
+        assert_eq!(vco / pll.q, 48_000_000);
+    }
+
+    #[test]
+    fn hsi_with_pll() {
+        let plan = CFGR::new().sysclk(SYSCLK_MAX.hz()).plan().unwrap();
+        assert!(plan.uses_pll());
+        assert_eq!(plan.sysclk().0, SYSCLK_MAX);
+    }
+
+    #[test]
+    fn hclk_prescaler() {
+        let plan = hse_84mhz().hclk(21.mhz()).plan().unwrap();
+        assert_eq!(plan.sysclk().0, 84_000_000);
+        assert_eq!(plan.hclk().0, 21_000_000);
+        assert_eq!(plan.pclk1().0, 21_000_000);
+    }
+
+    #[test]
+    fn sysclk_out_of_range() {
+        for &requested in &[1_000_000, SYSCLK_MAX + 1_000_000] {
+            assert_eq!(
+                CFGR::new().sysclk(requested.hz()).plan().err(),
+                Some(ClockError::OutOfRange {
+                    clock: Clock::Sysclk,
+                    requested,
+                    min: SYSCLK_MIN,
+                    max: SYSCLK_MAX,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn sysclk_not_exact() {
+        match hse_84mhz().sysclk(84_000_001.hz()).plan() {
+            Err(ClockError::NotExact {
+                clock: Clock::Sysclk,
+                requested: 84_000_001,
+                achieved,
+            }) => assert_ne!(achieved, 84_000_001),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn pclk1_out_of_range() {
+        match hse_84mhz().pclk1(84.mhz()).plan() {
+            Err(ClockError::OutOfRange {
+                clock: Clock::Pclk1,
+                requested: 84_000_000,
+                ..
+            }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn zero_bus_clock() {
+        for (clock, cfgr) in &[
+            (Clock::Hclk, CFGR::new().hclk(0.hz())),
+            (Clock::Pclk1, CFGR::new().pclk1(0.hz())),
+            (Clock::Pclk2, CFGR::new().pclk2(0.hz())),
+        ] {
+            match cfgr.plan() {
+                Err(ClockError::OutOfRange {
+                    clock: c,
+                    requested: 0,
+                    min: 1,
+                    ..
+                }) if c == *clock => {}
+                r => panic!("{:?}", r),
+            }
+        }
+    }
+
+    #[test]
+    fn lse_out_of_range() {
+        assert_eq!(
+            CFGR::new().use_lse_bypass(2.mhz()).plan().err(),
+            Some(ClockError::OutOfRange {
+                clock: Clock::Lse,
+                requested: 2_000_000,
+                min: 0,
+                max: 1_000_000,
+            })
+        );
+    }
+
+    #[test]
+    fn flash_latency() {
+        let plan = hse_84mhz().plan().unwrap();
+        assert_eq!(plan.flash_latency(), LATENCY_84MHZ.0);
+
+        let plan = hse_84mhz()
+            .supply_voltage(SupplyVoltage::V1_8To2_1)
+            .plan()
+            .unwrap();
+        assert_eq!(plan.flash_latency(), LATENCY_84MHZ.1);
+
+        // The wait states follow HCLK, not SYSCLK
+        let plan = hse_84mhz().hclk(21.mhz()).plan().unwrap();
+        assert_eq!(plan.flash_latency(), 0);
+    }
+
+    #[cfg(any(
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f415",
+        feature = "stm32f417"
+    ))]
+    #[test]
+    fn flash_latency_out_of_range() {
+        // At most 7 wait states of 20 MHz at 1.8 V
+        let plan = CFGR::new()
+            .use_hse(8.mhz())
+            .sysclk(168.mhz())
+            .supply_voltage(SupplyVoltage::V1_8To2_1)
+            .plan();
+        assert_eq!(
+            plan.err(),
+            Some(ClockError::OutOfRange {
+                clock: Clock::Hclk,
+                requested: 168_000_000,
+                min: 0,
+                max: 160_000_000,
+            })
+        );
+    }
+
+    #[cfg(any(
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    #[test]
+    fn over_drive_needs_2_1v() {
+        let cfgr = CFGR::new().use_hse(8.mhz()).sysclk(180.mhz());
+        assert_eq!(cfgr.plan().unwrap().sysclk().0, 180_000_000);
+        assert_eq!(
+            cfgr.supply_voltage(SupplyVoltage::V1_8To2_1).plan().err(),
+            Some(ClockError::OutOfRange {
+                clock: Clock::Sysclk,
+                requested: 180_000_000,
+                min: SYSCLK_MIN,
+                max: 168_000_000,
+            })
+        );
+    }
+
+    #[test]
+    fn voltage_scaling() {
+        #[cfg(any(
+            feature = "stm32f405",
+            feature = "stm32f407",
+            feature = "stm32f415",
+            feature = "stm32f417"
+        ))]
+        let expected = [(16_000_000, 0), (144_000_000, 0), (168_000_000, 1 << 14)];
+        #[cfg(feature = "stm32f401")]
+        let expected = [
+            (16_000_000, 0b01 << 14),
+            (60_000_000, 0b01 << 14),
+            (84_000_000, 0b10 << 14),
+        ];
+        #[cfg(any(
+            feature = "stm32f410",
+            feature = "stm32f411",
+            feature = "stm32f412",
+            feature = "stm32f413",
+            feature = "stm32f423"
+        ))]
+        let expected = [
+            (64_000_000, 0b01 << 14),
+            (84_000_000, 0b10 << 14),
+            (100_000_000, 0b11 << 14),
+        ];
+        #[cfg(any(
+            feature = "stm32f427",
+            feature = "stm32f429",
+            feature = "stm32f437",
+            feature = "stm32f439",
+            feature = "stm32f446",
+            feature = "stm32f469",
+            feature = "stm32f479"
+        ))]
+        let expected = [
+            (120_000_000, 0b01 << 14),
+            (144_000_000, 0b10 << 14),
+            (180_000_000, 0b11 << 14),
+        ];
+
+        for &(sysclk, vos) in &expected {
+            let (mask, bits) = voltage_scale(sysclk);
+            assert_eq!(bits, vos, "sysclk {}", sysclk);
+            assert_eq!(bits & !mask, 0);
+        }
+    }
+
+    #[cfg(any(
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f415",
+        feature = "stm32f417",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439"
+    ))]
+    #[test]
+    fn pll48clk_not_exact() {
+        // The VCO of 180 or 360 MHz needed for 90 MHz can't be divided down to 48 MHz and
+        // these devices can't take PLL48CLK from another PLL
+        match CFGR::new()
+            .use_hse(25.mhz())
+            .sysclk(90.mhz())
+            .require_pll48clk()
+            .plan()
+        {
+            Err(ClockError::NotExact {
+                clock: Clock::Pll48clk,
+                requested: 48_000_000,
+                ..
+            }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[cfg(any(
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f423",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    #[test]
+    fn pll48clk_from_alt_pll() {
+        let plan = CFGR::new()
+            .use_hse(25.mhz())
+            .sysclk(90.mhz())
+            .require_pll48clk()
+            .require_exact()
+            .plan()
+            .unwrap();
+        assert_eq!(plan.sysclk().0, 90_000_000);
+        assert_eq!(plan.pll48clk().map(|f| f.0), Some(48_000_000));
+    }
+
+    #[cfg(any(
+        feature = "stm32f401",
+        feature = "stm32f405",
+        feature = "stm32f407",
+        feature = "stm32f411",
+        feature = "stm32f412",
+        feature = "stm32f413",
+        feature = "stm32f415",
+        feature = "stm32f417",
+        feature = "stm32f423",
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    #[test]
+    fn plli2s() {
+        let plan = hse_84mhz().i2s_clk(86.mhz()).plan().unwrap();
+        assert_eq!(plan.sysclk().0, 84_000_000);
+        assert_eq!(plan.i2sclk().map(|f| f.0), Some(86_000_000));
+
+        match hse_84mhz().i2s_clk(86_000_001.hz()).plan() {
+            Err(ClockError::NotExact {
+                clock: Clock::I2sclk,
+                requested: 86_000_001,
+                achieved,
+            }) => assert_eq!(achieved, 86_000_000),
+            r => panic!("{:?}", r),
+        }
+
+        for &requested in &[0, 217_000_000] {
+            assert_eq!(
+                CFGR::new().i2s_clk(requested.hz()).plan().err(),
+                Some(ClockError::OutOfRange {
+                    clock: Clock::I2sclk,
+                    requested,
+                    min: 1,
+                    max: 216_000_000,
+                })
+            );
+        }
+
+        // An external I2S clock doesn't need the PLLI2S
+        let plan = CFGR::new().i2s_ckin(12_288_000.hz()).plan().unwrap();
+        assert_eq!(plan.i2sclk().map(|f| f.0), Some(12_288_000));
+    }
+
+    #[cfg(any(
+        feature = "stm32f427",
+        feature = "stm32f429",
+        feature = "stm32f437",
+        feature = "stm32f439",
+        feature = "stm32f446",
+        feature = "stm32f469",
+        feature = "stm32f479"
+    ))]
+    #[test]
+    fn pllsai() {
+        // 256 times 192 kHz can't be generated exactly, but within 1000 ppm
+        let cfgr = CFGR::new().use_hse(8.mhz()).sysclk(168.mhz()).sai_clk(49_152_000.hz());
+        let saiclk = cfgr.plan().unwrap().saiclk().unwrap().0;
+        assert!(error_ppm(49_152_000, saiclk).abs() < 1000, "saiclk {}", saiclk);
+
+        match cfgr.require_exact().plan() {
+            Err(ClockError::NotExact {
+                clock: Clock::Saiclk,
+                requested: 49_152_000,
+                achieved,
+            }) => assert_eq!(achieved, saiclk),
+            r => panic!("{:?}", r),
+        }
+
+        assert_eq!(
+            CFGR::new().sai_clk(0.hz()).plan().err(),
+            Some(ClockError::OutOfRange {
+                clock: Clock::Saiclk,
+                requested: 0,
+                min: 1,
+                max: 216_000_000,
+            })
+        );
+    }
 }
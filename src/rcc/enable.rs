@@ -0,0 +1,1012 @@
+//! Clock enable, reset and low-power enable bits of the peripherals
+
+use cortex_m::interrupt;
+
+use super::{BusClock, BusTimerClock, Clocks, Enable, LPEnable, Reset};
+use crate::stm32::*;
+use crate::time::Hertz;
+
+macro_rules! bus {
+    ($Bus:ident: ($enr:ident, $rstr:ident, $lpenr:ident), [
+        $($PER:ident: ($bit:expr, $rstbit:expr),)+
+    ]) => {
+        $(
+            impl Enable for $PER {
+                fn enable() {
+                    interrupt::free(|_| {
+                        // NOTE(unsafe) read-modify-write in a critical section
+                        let rcc = unsafe { &*RCC::ptr() };
+                        rcc.$enr.modify(|r, w| unsafe { w.bits(r.bits() | 1 << $bit) });
+                    });
+                }
+
+                fn disable() {
+                    interrupt::free(|_| {
+                        // NOTE(unsafe) read-modify-write in a critical section
+                        let rcc = unsafe { &*RCC::ptr() };
+                        rcc.$enr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << $bit)) });
+                    });
+                }
+
+                fn is_enabled() -> bool {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let rcc = unsafe { &*RCC::ptr() };
+                    rcc.$enr.read().bits() & 1 << $bit != 0
+                }
+            }
+
+            impl LPEnable for $PER {
+                fn low_power_enable() {
+                    interrupt::free(|_| {
+                        // NOTE(unsafe) read-modify-write in a critical section
+                        let rcc = unsafe { &*RCC::ptr() };
+                        rcc.$lpenr.modify(|r, w| unsafe { w.bits(r.bits() | 1 << $bit) });
+                    });
+                }
+
+                fn low_power_disable() {
+                    interrupt::free(|_| {
+                        // NOTE(unsafe) read-modify-write in a critical section
+                        let rcc = unsafe { &*RCC::ptr() };
+                        rcc.$lpenr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << $bit)) });
+                    });
+                }
+
+                fn is_low_power_enabled() -> bool {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let rcc = unsafe { &*RCC::ptr() };
+                    rcc.$lpenr.read().bits() & 1 << $bit != 0
+                }
+            }
+
+            impl Reset for $PER {
+                fn reset() {
+                    interrupt::free(|_| {
+                        // NOTE(unsafe) read-modify-write in a critical section
+                        let rcc = unsafe { &*RCC::ptr() };
+                        rcc.$rstr.modify(|r, w| unsafe { w.bits(r.bits() | 1 << $rstbit) });
+                        rcc.$rstr.modify(|r, w| unsafe { w.bits(r.bits() & !(1 << $rstbit)) });
+                    });
+                }
+            }
+
+            impl BusClock for $PER {
+                fn clock(clocks: &Clocks) -> Hertz {
+                    super::$Bus::clock(clocks)
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! bus_timer_clock {
+    ($Bus:ident: [$($TIM:ident,)+]) => {
+        $(
+            impl BusTimerClock for $TIM {
+                fn timer_clock(clocks: &Clocks) -> Hertz {
+                    super::$Bus::timer_clock(clocks)
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        GPIOA: (0, 0),
+        GPIOB: (1, 1),
+        GPIOC: (2, 2),
+        GPIOH: (7, 7),
+        CRC: (12, 12),
+        DMA1: (21, 21),
+        DMA2: (22, 22),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        GPIOD: (3, 3),
+        GPIOE: (4, 4),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        GPIOF: (5, 5),
+        GPIOG: (6, 6),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        GPIOI: (8, 8),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        GPIOJ: (9, 9),
+        GPIOK: (10, 10),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        TIM2: (0, 0),
+        TIM3: (1, 1),
+        TIM4: (2, 2),
+        SPI3: (15, 15),
+        I2C3: (23, 23),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        TIM5: (3, 3),
+        WWDG: (11, 11),
+        SPI2: (14, 14),
+        USART2: (17, 17),
+        I2C1: (21, 21),
+        I2C2: (22, 22),
+        PWR: (28, 28),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        TIM6: (4, 4),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        TIM7: (5, 5),
+        TIM12: (6, 6),
+        TIM13: (7, 7),
+        TIM14: (8, 8),
+        USART3: (18, 18),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        UART4: (19, 19),
+        UART5: (20, 20),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f413",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        UART7: (30, 30),
+        UART8: (31, 31),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        TIM1: (0, 0),
+        USART1: (4, 4),
+        USART6: (5, 5),
+        ADC1: (8, 8),
+        SPI1: (12, 12),
+        SYSCFG: (14, 14),
+        TIM9: (16, 16),
+        TIM11: (18, 18),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        TIM8: (1, 1),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        UART9: (6, 6),
+        UART10: (7, 7),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        ADC2: (9, 8),
+        ADC3: (10, 8),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        SPI4: (13, 13),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        TIM10: (17, 17),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        SPI5: (20, 20),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        SPI6: (21, 21),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        OTG_HS_GLOBAL: (29, 29),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f407",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        ETHERNET_MAC: (25, 25),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        DMA2D: (23, 23),
+    ]
+}
+
+#[cfg(feature = "stm32f410")]
+bus! {
+    AHB1: (ahb1enr, ahb1rstr, ahb1lpenr), [
+        RNG: (31, 31),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f407",
+    feature = "stm32f417",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB2: (ahb2enr, ahb2rstr, ahb2lpenr), [
+        DCMI: (0, 0),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB2: (ahb2enr, ahb2rstr, ahb2lpenr), [
+        RNG: (6, 6),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB2: (ahb2enr, ahb2rstr, ahb2lpenr), [
+        OTG_FS_GLOBAL: (7, 7),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423"
+))]
+bus! {
+    AHB3: (ahb3enr, ahb3rstr, ahb3lpenr), [
+        FSMC: (0, 0),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB3: (ahb3enr, ahb3rstr, ahb3lpenr), [
+        FMC: (0, 0),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    AHB3: (ahb3enr, ahb3rstr, ahb3lpenr), [
+        QUADSPI: (1, 1),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f410",
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        LPTIM1: (9, 9),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f410",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f423",
+    feature = "stm32f446"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        FMPI2C1: (24, 24),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        CAN1: (25, 25),
+        CAN2: (26, 26),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        CAN3: (27, 27),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB1: (apb1enr, apb1rstr, apb1lpenr), [
+        DAC: (29, 29),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        SDIO: (11, 11),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        SAI1: (22, 22),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        SAI: (22, 22),
+    ]
+}
+
+#[cfg(feature = "stm32f446")]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        SAI2: (23, 23),
+    ]
+}
+
+#[cfg(feature = "stm32f412")]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        DFSDM: (24, 24),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        DFSDM1: (24, 24),
+        DFSDM2: (25, 25),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f429",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus! {
+    APB2: (apb2enr, apb2rstr, apb2lpenr), [
+        LTDC: (26, 26),
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus_timer_clock! {
+    APB1: [
+        TIM2,
+        TIM3,
+        TIM4,
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus_timer_clock! {
+    APB1: [
+        TIM5,
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus_timer_clock! {
+    APB1: [
+        TIM6,
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus_timer_clock! {
+    APB1: [
+        TIM7,
+        TIM12,
+        TIM13,
+        TIM14,
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus_timer_clock! {
+    APB2: [
+        TIM1,
+        TIM9,
+        TIM11,
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus_timer_clock! {
+    APB2: [
+        TIM8,
+    ]
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+bus_timer_clock! {
+    APB2: [
+        TIM10,
+    ]
+}
@@ -2,6 +2,7 @@ use core::fmt;
 use core::marker::PhantomData;
 use core::ptr;
 
+use embedded_hal::digital::v2::OutputPin;
 use embedded_hal::serial;
 use embedded_hal::prelude::*;
 use nb::block;
@@ -25,7 +26,7 @@ use nb::block;
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-use crate::stm32::{RCC, USART1, USART2, USART6};
+use crate::stm32::{DMA1, DMA2, USART1, USART2, USART6};
 
 #[cfg(any(
     feature = "stm32f405",
@@ -409,13 +410,18 @@ use crate::gpio::gpiog::{PG14, PG9};
 ))]
 use crate::gpio::gpiog::{PG11, PG12};
 
+use crate::dma::{
+    self, config::DmaConfig, Channel, DMASet, MemoryToPeripheral, PeriAddress,
+    PeripheralToMemory, Stream, Transfer,
+};
 use crate::gpio::{Alternate, AF7, AF8};
 #[cfg(any(
     feature = "stm32f413",
     feature = "stm32f423"
 ))]
 use crate::gpio::AF11;
-use crate::rcc::Clocks;
+use crate::rcc::{BusClock, Clocks, Enable, Reset};
+use crate::time::Bps;
 
 /// Serial error
 #[derive(Debug)]
@@ -440,6 +446,8 @@ pub enum Event {
     Txe,
     /// Idle line state detected
     Idle,
+    /// Transmission complete
+    Tc,
 }
 
 pub mod config {
@@ -457,6 +465,18 @@ pub mod config {
         ParityOdd,
     }
 
+    /// Hardware flow control
+    pub enum FlowControl {
+        /// No flow control
+        None,
+        /// Only RTS, the receiver deasserts RTS when it can't accept more data
+        Rts,
+        /// Only CTS, the transmitter waits for CTS to be asserted
+        Cts,
+        /// Both RTS and CTS
+        RtsCts,
+    }
+
     pub enum StopBits {
         #[doc = "1 stop bit"]
         STOP1,
@@ -473,6 +493,7 @@ pub mod config {
         pub wordlength: WordLength,
         pub parity: Parity,
         pub stopbits: StopBits,
+        pub flowcontrol: FlowControl,
     }
 
     impl Config {
@@ -510,6 +531,12 @@ pub mod config {
             self.stopbits = stopbits;
             self
         }
+
+        /// Requires the matching CTS/RTS pins to be passed to the constructor
+        pub fn flow_control(mut self, flowcontrol: FlowControl) -> Self {
+            self.flowcontrol = flowcontrol;
+            self
+        }
     }
 
     #[derive(Debug)]
@@ -523,14 +550,28 @@ pub mod config {
                 wordlength: WordLength::DataBits8,
                 parity: Parity::ParityNone,
                 stopbits: StopBits::STOP1,
+                flowcontrol: FlowControl::None,
             }
         }
     }
 }
 
-pub trait Pins<USART> {}
+pub trait Pins<USART> {
+    /// True if a CTS pin is part of the set
+    const CTS: bool = false;
+    /// True if a RTS pin is part of the set
+    const RTS: bool = false;
+}
 pub trait PinTx<USART> {}
 pub trait PinRx<USART> {}
+pub trait PinCts<USART> {
+    /// False for the `NoCts` filler
+    const PRESENT: bool = true;
+}
+pub trait PinRts<USART> {
+    /// False for the `NoRts` filler
+    const PRESENT: bool = true;
+}
 
 impl<USART, TX, RX> Pins<USART> for (TX, RX)
 where
@@ -538,10 +579,287 @@ where
     RX: PinRx<USART>,
 {}
 
+impl<USART, TX, RX, CTS, RTS> Pins<USART> for (TX, RX, CTS, RTS)
+where
+    TX: PinTx<USART>,
+    RX: PinRx<USART>,
+    CTS: PinCts<USART>,
+    RTS: PinRts<USART>,
+{
+    const CTS: bool = CTS::PRESENT;
+    const RTS: bool = RTS::PRESENT;
+}
+
 /// A filler type for when the Tx pin is unnecessary
 pub struct NoTx;
 /// A filler type for when the Rx pin is unnecessary
 pub struct NoRx;
+/// A filler type for when the CTS pin is unnecessary
+pub struct NoCts;
+/// A filler type for when the RTS pin is unnecessary
+pub struct NoRts;
+
+impl<USART> PinCts<USART> for NoCts {
+    const PRESENT: bool = false;
+}
+impl<USART> PinRts<USART> for NoRts {
+    const PRESENT: bool = false;
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinCts<USART1> for crate::gpio::gpioa::PA11<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinRts<USART1> for crate::gpio::gpioa::PA12<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinCts<USART2> for crate::gpio::gpioa::PA0<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinRts<USART2> for crate::gpio::gpioa::PA1<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinCts<USART2> for crate::gpio::gpiod::PD3<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinRts<USART2> for crate::gpio::gpiod::PD4<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinCts<USART3> for crate::gpio::gpiob::PB13<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinRts<USART3> for crate::gpio::gpiob::PB14<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinCts<USART3> for crate::gpio::gpiod::PD11<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinRts<USART3> for crate::gpio::gpiod::PD12<Alternate<AF7>> {}
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinCts<USART6> for crate::gpio::gpiog::PG13<Alternate<AF8>> {}
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinCts<USART6> for crate::gpio::gpiog::PG15<Alternate<AF8>> {}
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinRts<USART6> for crate::gpio::gpiog::PG8<Alternate<AF8>> {}
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+impl PinRts<USART6> for crate::gpio::gpiog::PG12<Alternate<AF8>> {}
 
 #[cfg(any(
     feature = "stm32f401",
@@ -1523,9 +1841,102 @@ pub struct Tx<USART> {
     _usart: PhantomData<USART>,
 }
 
+/// Serial port driving a RS-485 transceiver
+///
+/// The driver enable (DE) pin is asserted before a byte is written and released once the
+/// transmission is complete, either by `flush` or by `on_interrupt`. Every write enables the
+/// `Event::Tc` interrupt, which `on_interrupt` disables again when it releases the bus.
+pub struct Rs485<USART, PINS, DE> {
+    serial: Serial<USART, PINS>,
+    de: DE,
+}
+
+/// Fixed capacity FIFO of bytes backed by a `'static` slice
+struct RingBuffer {
+    buf: &'static mut [u8],
+    read: usize,
+    len: usize,
+}
+
+impl RingBuffer {
+    fn new(buf: &'static mut [u8]) -> Self {
+        RingBuffer {
+            buf,
+            read: 0,
+            len: 0,
+        }
+    }
+
+    /// Appends `byte`, returns false if the buffer is full
+    fn push(&mut self, byte: u8) -> bool {
+        if self.len == self.buf.len() {
+            return false;
+        }
+        let write = (self.read + self.len) % self.buf.len();
+        self.buf[write] = byte;
+        self.len += 1;
+        true
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let byte = self.buf[self.read];
+        self.read = (self.read + 1) % self.buf.len();
+        self.len -= 1;
+        Some(byte)
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == self.buf.len()
+    }
+
+    fn release(self) -> &'static mut [u8] {
+        self.buf
+    }
+}
+
+/// Interrupt driven serial port with receive and transmit ring buffers
+///
+/// The USART interrupt handler must call [`on_interrupt`](#method.on_interrupt), which moves
+/// bytes between the data register and the ring buffers. Since the same value is used from the
+/// handler and from the application, it will usually be stored in a
+/// `cortex_m::interrupt::Mutex<RefCell<Option<BufferedSerial<..>>>>`.
+///
+/// Receive errors (parity, framing, noise, overrun) are kept until the next call to `read`,
+/// which returns them once. Bytes received while the receive buffer is full are dropped and
+/// reported as `Error::Overrun`.
+pub struct BufferedSerial<USART, PINS> {
+    serial: Serial<USART, PINS>,
+    rx: RingBuffer,
+    tx: RingBuffer,
+    error: Option<Error>,
+}
+
+impl<USART, PINS> BufferedSerial<USART, PINS> {
+    /// Number of received bytes that can be read without blocking
+    pub fn bytes_available(&self) -> usize {
+        self.rx.len
+    }
+
+    /// Number of bytes queued for transmission
+    pub fn bytes_pending(&self) -> usize {
+        self.tx.len
+    }
+
+    /// Reads a received byte or the last receive error
+    pub fn read(&mut self) -> nb::Result<u8, Error> {
+        if let Some(error) = self.error.take() {
+            return Err(nb::Error::Other(error));
+        }
+        self.rx.pop().ok_or(nb::Error::WouldBlock)
+    }
+}
+
 macro_rules! halUsartImpl {
     ($(
-        $USARTX:ident: ($usartX:ident, $apbXenr:ident, $usartXen:ident,  $pclkX:ident),
+        $USARTX:ident: ($usartX:ident),
     )+) => {
         $(
             impl<PINS> Serial<$USARTX, PINS> {
@@ -1540,14 +1951,22 @@ macro_rules! halUsartImpl {
                 {
                     use self::config::*;
 
-                    // NOTE(unsafe) This executes only during initialisation
-                    let rcc = unsafe { &(*RCC::ptr()) };
+                    let (cts, rts) = match config.flowcontrol {
+                        FlowControl::None => (false, false),
+                        FlowControl::Rts => (false, true),
+                        FlowControl::Cts => (true, false),
+                        FlowControl::RtsCts => (true, true),
+                    };
+                    if (cts && !PINS::CTS) || (rts && !PINS::RTS) {
+                        return Err(InvalidConfig);
+                    }
 
-                    // Enable clock for USART
-                    rcc.$apbXenr.modify(|_, w| w.$usartXen().set_bit());
+                    // Enable clock for USART and reset it
+                    $USARTX::enable();
+                    $USARTX::reset();
 
                     // Calculate correct baudrate divisor on the fly
-                    let div = (clocks.$pclkX().0 + config.baudrate.0 / 2)
+                    let div = ($USARTX::clock(&clocks).0 + config.baudrate.0 / 2)
                         / config.baudrate.0;
                     usart.brr.write(|w| unsafe { w.bits(div) });
 
@@ -1579,7 +1998,28 @@ macro_rules! halUsartImpl {
                             })
                     });
 
-                    Ok(Serial { usart, pins }.config_stop(config))
+                    Ok(Serial { usart, pins }
+                        .config_flow_control(cts, rts)
+                        .config_stop(config))
+                }
+
+                /// Changes the baud rate, also needed after the clocks were reconfigured with
+                /// `CFGR::freeze`
+                ///
+                /// Returns `WouldBlock` until an ongoing transmission has completed, which
+                /// never happens while the transmitter is held off by CTS.
+                pub fn set_baudrate(
+                    &mut self,
+                    baudrate: Bps,
+                    clocks: Clocks,
+                ) -> nb::Result<(), Error> {
+                    if self.usart.sr.read().tc().bit_is_clear() {
+                        return Err(nb::Error::WouldBlock);
+                    }
+
+                    let div = ($USARTX::clock(&clocks).0 + baudrate.0 / 2) / baudrate.0;
+                    self.usart.brr.write(|w| unsafe { w.bits(div) });
+                    Ok(())
                 }
 
                 /// Starts listening for an interrupt event
@@ -1594,6 +2034,9 @@ macro_rules! halUsartImpl {
                         Event::Idle => {
                             self.usart.cr1.modify(|_, w| w.idleie().set_bit())
                         },
+                        Event::Tc => {
+                            self.usart.cr1.modify(|_, w| w.tcie().set_bit())
+                        },
                     }
                 }
 
@@ -1609,6 +2052,9 @@ macro_rules! halUsartImpl {
                         Event::Idle => {
                             self.usart.cr1.modify(|_, w| w.idleie().clear_bit())
                         },
+                        Event::Tc => {
+                            self.usart.cr1.modify(|_, w| w.tcie().clear_bit())
+                        },
                     }
                 }
 
@@ -1627,6 +2073,20 @@ macro_rules! halUsartImpl {
                     unsafe { (*$USARTX::ptr()).sr.read().rxne().bit_is_set() }
                 }
 
+                /// Return true if the transmission of the last byte is complete
+                pub fn is_tc(& self) -> bool {
+                    unsafe { (*$USARTX::ptr()).sr.read().tc().bit_is_set() }
+                }
+
+                /// Turns the serial port into a RS-485 transmitter that asserts `de` while sending
+                pub fn with_driver_enable<DE>(self, mut de: DE) -> Rs485<$USARTX, PINS, DE>
+                where
+                    DE: OutputPin,
+                {
+                    let _ = de.set_low();
+                    Rs485 { serial: self, de }
+                }
+
                 pub fn split(self) -> (Tx<$USARTX>, Rx<$USARTX>) {
                     (
                         Tx {
@@ -1642,6 +2102,336 @@ macro_rules! halUsartImpl {
                 }
             }
 
+            unsafe impl PeriAddress for Rx<$USARTX> {
+                type MemSize = u8;
+
+                fn address(&self) -> u32 {
+                    unsafe { &(*$USARTX::ptr()).dr as *const _ as u32 }
+                }
+            }
+
+            unsafe impl PeriAddress for Tx<$USARTX> {
+                type MemSize = u8;
+
+                fn address(&self) -> u32 {
+                    unsafe { &(*$USARTX::ptr()).dr as *const _ as u32 }
+                }
+            }
+
+            impl Rx<$USARTX> {
+                /// Lets the receiver issue DMA requests
+                pub fn enable_dma(&mut self) {
+                    // NOTE(unsafe) atomic modify of a register only used by the receiver
+                    unsafe { (*$USARTX::ptr()).cr3.modify(|_, w| w.dmar().set_bit()) }
+                }
+
+                /// Stops the receiver from issuing DMA requests
+                pub fn disable_dma(&mut self) {
+                    // NOTE(unsafe) atomic modify of a register only used by the receiver
+                    unsafe { (*$USARTX::ptr()).cr3.modify(|_, w| w.dmar().clear_bit()) }
+                }
+
+                /// Receives exactly `buf.len()` bytes into `buf` using DMA
+                ///
+                /// Memory increment is always enabled and circular mode always disabled, the
+                /// other settings of `config` (priority, interrupts, FIFO) are applied as is.
+                pub fn read_exact_dma<STREAM, CHANNEL, BUF>(
+                    self,
+                    stream: STREAM,
+                    buf: &'static mut BUF,
+                    config: DmaConfig,
+                ) -> Transfer<STREAM, CHANNEL, Self, PeripheralToMemory, BUF>
+                where
+                    STREAM: Stream,
+                    CHANNEL: Channel,
+                    Self: DMASet<STREAM, CHANNEL, PeripheralToMemory>,
+                    BUF: AsMut<[u8]>,
+                {
+                    let config = config.memory_increment(true).circular(false);
+                    let mut transfer = Transfer::init(stream, self, buf, config);
+                    transfer.start(|rx| rx.enable_dma());
+                    transfer
+                }
+
+                /// Continuously receives into `buf` using DMA
+                ///
+                /// The stream runs in circular mode, so `buf` is refilled from the start once it
+                /// is full. Use the half-transfer and transfer-complete flags, or
+                /// [`Transfer::peek`](../dma/struct.Transfer.html#method.peek), to process one
+                /// half of the buffer while the other half is being filled.
+                pub fn read_circular_dma<STREAM, CHANNEL, BUF>(
+                    self,
+                    stream: STREAM,
+                    buf: &'static mut BUF,
+                    config: DmaConfig,
+                ) -> Transfer<STREAM, CHANNEL, Self, PeripheralToMemory, BUF>
+                where
+                    STREAM: Stream,
+                    CHANNEL: Channel,
+                    Self: DMASet<STREAM, CHANNEL, PeripheralToMemory>,
+                    BUF: AsMut<[u8]>,
+                {
+                    let config = config.memory_increment(true).circular(true);
+                    let mut transfer = Transfer::init(stream, self, buf, config);
+                    transfer.start(|rx| rx.enable_dma());
+                    transfer
+                }
+            }
+
+            impl Tx<$USARTX> {
+                /// Changes the baud rate of the transmitter and the receiver, also needed after
+                /// the clocks were reconfigured with `CFGR::freeze`
+                ///
+                /// Returns `WouldBlock` until an ongoing transmission has completed, see
+                /// `Serial::set_baudrate`.
+                pub fn set_baudrate(
+                    &mut self,
+                    baudrate: Bps,
+                    clocks: Clocks,
+                ) -> nb::Result<(), Error> {
+                    // NOTE(unsafe) BRR isn't written by the receiver
+                    let usart = unsafe { &*$USARTX::ptr() };
+
+                    if usart.sr.read().tc().bit_is_clear() {
+                        return Err(nb::Error::WouldBlock);
+                    }
+
+                    let div = ($USARTX::clock(&clocks).0 + baudrate.0 / 2) / baudrate.0;
+                    usart.brr.write(|w| unsafe { w.bits(div) });
+                    Ok(())
+                }
+
+                /// Lets the transmitter issue DMA requests
+                pub fn enable_dma(&mut self) {
+                    // NOTE(unsafe) atomic modify of a register only used by the transmitter
+                    unsafe { (*$USARTX::ptr()).cr3.modify(|_, w| w.dmat().set_bit()) }
+                }
+
+                /// Stops the transmitter from issuing DMA requests
+                pub fn disable_dma(&mut self) {
+                    // NOTE(unsafe) atomic modify of a register only used by the transmitter
+                    unsafe { (*$USARTX::ptr()).cr3.modify(|_, w| w.dmat().clear_bit()) }
+                }
+
+                /// Sends all of `buf` using DMA
+                ///
+                /// Memory increment is always enabled and circular mode always disabled, the
+                /// other settings of `config` (priority, interrupts, FIFO) are applied as is.
+                /// Once the transfer is complete the last byte may still be shifted out, use
+                /// `flush` before disabling the transmitter.
+                pub fn write_all_dma<STREAM, CHANNEL, BUF>(
+                    self,
+                    stream: STREAM,
+                    buf: &'static mut BUF,
+                    config: DmaConfig,
+                ) -> Transfer<STREAM, CHANNEL, Self, MemoryToPeripheral, BUF>
+                where
+                    STREAM: Stream,
+                    CHANNEL: Channel,
+                    Self: DMASet<STREAM, CHANNEL, MemoryToPeripheral>,
+                    BUF: AsMut<[u8]>,
+                {
+                    let config = config.memory_increment(true).circular(false);
+                    let mut transfer = Transfer::init(stream, self, buf, config);
+                    transfer.start(|tx| {
+                        // Clear TC so that `flush` waits for the DMA data to be sent
+                        // NOTE(unsafe) atomic write, TC is cleared by writing 0 to it
+                        unsafe { (*$USARTX::ptr()).sr.modify(|_, w| w.tc().clear_bit()) }
+                        tx.enable_dma();
+                    });
+                    transfer
+                }
+            }
+
+            impl<PINS, DE> Rs485<$USARTX, PINS, DE>
+            where
+                DE: OutputPin,
+            {
+                /// Releases the driver enable pin once the last byte has been sent
+                ///
+                /// Call this from the USART interrupt handler to release the bus without
+                /// blocking in `flush`.
+                pub fn on_interrupt(&mut self) {
+                    if self.serial.is_tc() {
+                        let _ = self.de.set_low();
+                        self.serial.unlisten(Event::Tc);
+                    }
+                }
+
+                /// Starts listening for an interrupt event
+                ///
+                /// `Event::Tc` is managed by the driver.
+                pub fn listen(&mut self, event: Event) {
+                    self.serial.listen(event)
+                }
+
+                /// Stops listening for an interrupt event
+                pub fn unlisten(&mut self, event: Event) {
+                    self.serial.unlisten(event)
+                }
+
+                /// Changes the baud rate, see `Serial::set_baudrate`
+                pub fn set_baudrate(
+                    &mut self,
+                    baudrate: Bps,
+                    clocks: Clocks,
+                ) -> nb::Result<(), Error> {
+                    self.serial.set_baudrate(baudrate, clocks)
+                }
+
+                /// Releases the serial port and the driver enable pin
+                pub fn release(mut self) -> (Serial<$USARTX, PINS>, DE) {
+                    let _ = self.de.set_low();
+                    self.serial.unlisten(Event::Tc);
+                    (self.serial, self.de)
+                }
+            }
+
+            impl<PINS, DE> serial::Read<u8> for Rs485<$USARTX, PINS, DE> {
+                type Error = Error;
+
+                fn read(&mut self) -> nb::Result<u8, Error> {
+                    self.serial.read()
+                }
+            }
+
+            impl<PINS, DE> serial::Write<u8> for Rs485<$USARTX, PINS, DE>
+            where
+                DE: OutputPin,
+            {
+                type Error = Error;
+
+                /// Waits for the last byte to be sent, then releases the bus
+                fn flush(&mut self) -> nb::Result<(), Self::Error> {
+                    self.serial.flush()?;
+                    let _ = self.de.set_low();
+                    self.serial.unlisten(Event::Tc);
+                    Ok(())
+                }
+
+                /// Asserts the driver enable pin and sends `byte`
+                ///
+                /// Enables the `Event::Tc` interrupt so that `on_interrupt` releases the bus.
+                fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+                    let _ = self.de.set_high();
+                    self.serial.write(byte)?;
+                    self.serial.listen(Event::Tc);
+                    Ok(())
+                }
+            }
+
+            impl<PINS> BufferedSerial<$USARTX, PINS> {
+                /// Wraps `serial` and starts listening for received data
+                ///
+                /// # Panics
+                ///
+                /// Panics if one of the buffers is empty.
+                pub fn new(
+                    mut serial: Serial<$USARTX, PINS>,
+                    rx_buf: &'static mut [u8],
+                    tx_buf: &'static mut [u8],
+                ) -> Self {
+                    assert!(!rx_buf.is_empty() && !tx_buf.is_empty());
+
+                    serial.listen(Event::Rxne);
+                    BufferedSerial {
+                        serial,
+                        rx: RingBuffer::new(rx_buf),
+                        tx: RingBuffer::new(tx_buf),
+                        error: None,
+                    }
+                }
+
+                /// Services the USART, must be called from its interrupt handler
+                pub fn on_interrupt(&mut self) {
+                    match self.serial.read() {
+                        Ok(byte) => {
+                            if !self.rx.push(byte) {
+                                self.error = Some(Error::Overrun);
+                            }
+                        }
+                        Err(nb::Error::Other(error)) => self.error = Some(error),
+                        Err(nb::Error::WouldBlock) => {}
+                    }
+
+                    if self.serial.is_txe() {
+                        match self.tx.pop() {
+                            Some(byte) => {
+                                // TXE is set so this can't block
+                                let _ = self.serial.write(byte);
+                            }
+                            None => self.serial.unlisten(Event::Txe),
+                        }
+                    }
+                }
+
+                /// Queues `byte` for transmission
+                pub fn write(&mut self, byte: u8) -> nb::Result<(), Error> {
+                    if self.tx.is_full() {
+                        return Err(nb::Error::WouldBlock);
+                    }
+                    self.tx.push(byte);
+                    self.serial.listen(Event::Txe);
+                    Ok(())
+                }
+
+                /// Queues as many bytes of `bytes` as fit, returns how many were queued
+                pub fn write_bytes(&mut self, bytes: &[u8]) -> usize {
+                    let queued = bytes.iter().take_while(|&&byte| self.tx.push(byte)).count();
+                    if queued != 0 {
+                        self.serial.listen(Event::Txe);
+                    }
+                    queued
+                }
+
+                /// Waits until all queued bytes have been sent
+                pub fn flush(&mut self) -> nb::Result<(), Error> {
+                    if self.tx.len != 0 {
+                        return Err(nb::Error::WouldBlock);
+                    }
+                    serial::Write::flush(&mut self.serial)
+                }
+
+                /// Changes the baud rate, see `Serial::set_baudrate`
+                ///
+                /// Bytes that are still queued are sent with the new baud rate, `flush` first to
+                /// avoid this.
+                pub fn set_baudrate(
+                    &mut self,
+                    baudrate: Bps,
+                    clocks: Clocks,
+                ) -> nb::Result<(), Error> {
+                    self.serial.set_baudrate(baudrate, clocks)
+                }
+
+                /// Stops listening for interrupts and releases the serial port and the buffers
+                pub fn release(mut self) -> (Serial<$USARTX, PINS>, &'static mut [u8], &'static mut [u8]) {
+                    self.serial.unlisten(Event::Rxne);
+                    self.serial.unlisten(Event::Txe);
+                    (self.serial, self.rx.release(), self.tx.release())
+                }
+            }
+
+            impl<PINS> serial::Read<u8> for BufferedSerial<$USARTX, PINS> {
+                type Error = Error;
+
+                fn read(&mut self) -> nb::Result<u8, Error> {
+                    BufferedSerial::read(self)
+                }
+            }
+
+            impl<PINS> serial::Write<u8> for BufferedSerial<$USARTX, PINS> {
+                type Error = Error;
+
+                fn flush(&mut self) -> nb::Result<(), Self::Error> {
+                    BufferedSerial::flush(self)
+                }
+
+                fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+                    BufferedSerial::write(self, byte)
+                }
+            }
+
             impl<PINS> serial::Read<u8> for Serial<$USARTX, PINS> {
                 type Error = Error;
 
@@ -1738,10 +2528,15 @@ macro_rules! halUsartImpl {
 
 macro_rules! halUsart {
     ($(
-        $USARTX:ident: ($usartX:ident, $apbXenr:ident, $usartXen:ident, $pclkX:ident),
+        $USARTX:ident: ($usartX:ident),
     )+) => {
         $(
         impl<PINS> Serial<$USARTX, PINS> {
+            fn config_flow_control(self, cts: bool, rts: bool) -> Self {
+                self.usart.cr3.modify(|_, w| w.ctse().bit(cts).rtse().bit(rts));
+                self
+            }
+
             fn config_stop(self, config: config::Config) -> Self {
                 use crate::stm32::usart1::cr2::STOP_A;
                 use self::config::*;
@@ -1760,7 +2555,7 @@ macro_rules! halUsart {
         )+
 
         halUsartImpl! {
-            $( $USARTX: ($usartX, $apbXenr, $usartXen, $pclkX), )+
+            $( $USARTX: ($usartX), )+
         }
     }
 }
@@ -1780,10 +2575,16 @@ macro_rules! halUsart {
 ))]
 macro_rules! halUart {
     ($(
-        $USARTX:ident: ($usartX:ident, $apbXenr:ident, $usartXen:ident, $pclkX:ident),
+        $USARTX:ident: ($usartX:ident),
     )+) => {
         $(
         impl<PINS> Serial<$USARTX, PINS> {
+            // UART4 and UART5 have no flow control and no CTS/RTS pins, so the pins check in
+            // the constructor already rejected any flow control configuration
+            fn config_flow_control(self, _cts: bool, _rts: bool) -> Self {
+                self
+            }
+
             fn config_stop(self, config: config::Config) -> Self {
                 use crate::stm32::uart4::cr2::STOP_A;
                 use self::config::*;
@@ -1802,7 +2603,7 @@ macro_rules! halUart {
         )+
 
         halUsartImpl! {
-            $( $USARTX: ($usartX, $apbXenr, $usartXen, $pclkX), )+
+            $( $USARTX: ($usartX), )+
         }
     }
 }
@@ -1827,9 +2628,9 @@ macro_rules! halUart {
     feature = "stm32f479"
 ))]
 halUsart! {
-    USART1: (usart1, apb2enr, usart1en, pclk2),
-    USART2: (usart2, apb1enr, usart2en, pclk1),
-    USART6: (usart6, apb2enr, usart6en, pclk2),
+    USART1: (usart1),
+    USART2: (usart2),
+    USART6: (usart6),
 }
 
 #[cfg(any(
@@ -1849,7 +2650,7 @@ halUsart! {
     feature = "stm32f479"
 ))]
 halUsart! {
-    USART3: (usart3, apb1enr, usart3en, pclk1),
+    USART3: (usart3),
 }
 
 #[cfg(any(
@@ -1866,8 +2667,8 @@ halUsart! {
     feature = "stm32f479"
 ))]
 halUart! {
-    UART4: (uart4, apb1enr, uart4en, pclk1),
-    UART5: (uart5, apb1enr, uart5en, pclk1),
+    UART4: (uart4),
+    UART5: (uart5),
 }
 
 #[cfg(any(
@@ -1875,8 +2676,8 @@ halUart! {
     feature = "stm32f423"
 ))]
 halUsart! {
-    UART4: (uart4, apb1enr, uart4en, pclk1),
-    UART5: (uart5, apb1enr, uart5en, pclk1),
+    UART4: (uart4),
+    UART5: (uart5),
 }
 
 #[cfg(any(
@@ -1890,8 +2691,8 @@ halUsart! {
     feature = "stm32f479"
 ))]
 halUsart! {
-    UART7: (uart7, apb1enr, uart7en, pclk1),
-    UART8: (uart8, apb1enr, uart8en, pclk1),
+    UART7: (uart7),
+    UART8: (uart8),
 }
 
 #[cfg(any(
@@ -1899,8 +2700,121 @@ halUsart! {
     feature = "stm32f423"
 ))]
 halUsart! {
-    UART9: (uart9, apb2enr, uart9en, pclk2),
-    UART10: (uart10, apb2enr, uart10en, pclk2),
+    UART9: (uart9),
+    UART10: (uart10),
+}
+
+macro_rules! dma_map {
+    ($(($Stream:ident<$DMA:ident>, $Channel:ident, $Peripheral:ty, $Direction:ty),)+) => {
+        $(
+            unsafe impl DMASet<dma::$Stream<$DMA>, dma::$Channel, $Direction> for $Peripheral {}
+        )+
+    };
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f410",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+dma_map! {
+    (Stream7<DMA2>, Channel4, Tx<USART1>, MemoryToPeripheral),
+    (Stream2<DMA2>, Channel4, Rx<USART1>, PeripheralToMemory),
+    (Stream5<DMA2>, Channel4, Rx<USART1>, PeripheralToMemory),
+    (Stream6<DMA1>, Channel4, Tx<USART2>, MemoryToPeripheral),
+    (Stream5<DMA1>, Channel4, Rx<USART2>, PeripheralToMemory),
+    (Stream6<DMA2>, Channel5, Tx<USART6>, MemoryToPeripheral),
+    (Stream7<DMA2>, Channel5, Tx<USART6>, MemoryToPeripheral),
+    (Stream1<DMA2>, Channel5, Rx<USART6>, PeripheralToMemory),
+    (Stream2<DMA2>, Channel5, Rx<USART6>, PeripheralToMemory),
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+dma_map! {
+    (Stream3<DMA1>, Channel4, Tx<USART3>, MemoryToPeripheral),
+    (Stream4<DMA1>, Channel7, Tx<USART3>, MemoryToPeripheral),
+    (Stream1<DMA1>, Channel4, Rx<USART3>, PeripheralToMemory),
+}
+
+#[cfg(any(
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+dma_map! {
+    (Stream4<DMA1>, Channel4, Tx<UART4>, MemoryToPeripheral),
+    (Stream2<DMA1>, Channel4, Rx<UART4>, PeripheralToMemory),
+    (Stream7<DMA1>, Channel4, Tx<UART5>, MemoryToPeripheral),
+    (Stream0<DMA1>, Channel4, Rx<UART5>, PeripheralToMemory),
+}
+
+#[cfg(any(
+    feature = "stm32f413",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+dma_map! {
+    (Stream1<DMA1>, Channel5, Tx<UART7>, MemoryToPeripheral),
+    (Stream3<DMA1>, Channel5, Rx<UART7>, PeripheralToMemory),
+    (Stream0<DMA1>, Channel5, Tx<UART8>, MemoryToPeripheral),
+    (Stream6<DMA1>, Channel5, Rx<UART8>, PeripheralToMemory),
+}
+
+#[cfg(any(
+    feature = "stm32f413",
+    feature = "stm32f423"
+))]
+dma_map! {
+    (Stream0<DMA2>, Channel1, Tx<UART9>, MemoryToPeripheral),
+    (Stream7<DMA2>, Channel0, Rx<UART9>, PeripheralToMemory),
+    (Stream6<DMA2>, Channel8, Tx<UART10>, MemoryToPeripheral),
+    (Stream7<DMA2>, Channel6, Tx<UART10>, MemoryToPeripheral),
+    (Stream0<DMA2>, Channel5, Rx<UART10>, PeripheralToMemory),
+    (Stream3<DMA2>, Channel9, Rx<UART10>, PeripheralToMemory),
 }
 
 impl<USART> fmt::Write for Tx<USART>
@@ -1916,3 +2830,61 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RingBuffer;
+
+    fn ring_buffer(len: usize) -> RingBuffer {
+        RingBuffer::new(Box::leak(vec![0; len].into_boxed_slice()))
+    }
+
+    #[test]
+    fn push_pop_across_wrap() {
+        let mut rb = ring_buffer(4);
+        for byte in 1..=3 {
+            assert!(rb.push(byte));
+        }
+        assert_eq!(rb.pop(), Some(1));
+        assert_eq!(rb.pop(), Some(2));
+
+        // the write index wraps around the end of the slice
+        for byte in 4..=6 {
+            assert!(rb.push(byte));
+        }
+        assert!(rb.is_full());
+        for byte in 3..=6 {
+            assert_eq!(rb.pop(), Some(byte));
+        }
+        assert_eq!(rb.pop(), None);
+        assert_eq!(rb.len, 0);
+    }
+
+    #[test]
+    fn push_into_full() {
+        let mut rb = ring_buffer(3);
+        for byte in 1..=3 {
+            assert!(rb.push(byte));
+        }
+        assert!(rb.is_full());
+        assert!(!rb.push(4));
+        assert_eq!(rb.len, 3);
+
+        // the rejected byte didn't overwrite anything
+        for byte in 1..=3 {
+            assert_eq!(rb.pop(), Some(byte));
+        }
+        assert!(!rb.is_full());
+    }
+
+    #[test]
+    fn pop_from_empty() {
+        let mut rb = ring_buffer(2);
+        assert_eq!(rb.pop(), None);
+
+        assert!(rb.push(7));
+        assert_eq!(rb.pop(), Some(7));
+        assert_eq!(rb.pop(), None);
+        assert_eq!(rb.len, 0);
+    }
+}
@@ -24,7 +24,7 @@ use nb;
     feature = "stm32f469",
     feature = "stm32f479"
 ))]
-use crate::stm32::{spi1, SPI1, SPI2, RCC};
+use crate::stm32::{spi1, SPI1, SPI2};
 
 #[cfg(any(
     feature = "stm32f401",
@@ -352,7 +352,7 @@ use crate::gpio::AF7;
 ))]
 use crate::gpio::{Alternate, AF5, AF6};
 
-use crate::rcc::Clocks;
+use crate::rcc::{BusClock, Clocks, Enable, Reset};
 use crate::time::Hertz;
 
 /// SPI error
@@ -746,13 +746,11 @@ impl<PINS> Spi<SPI1, PINS> {
     where
         PINS: Pins<SPI1>
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
+        // Enable clock for SPI and reset it
+        SPI1::enable();
+        SPI1::reset();
 
-        // Enable clock for SPI
-        rcc.apb2enr.modify(|_, w| w.spi1en().set_bit());
-
-        Spi { spi, pins }.init(mode, freq, clocks.pclk2())
+        Spi { spi, pins }.init(mode, freq, SPI1::clock(&clocks))
     }
 }
 
@@ -780,13 +778,11 @@ impl<PINS> Spi<SPI2, PINS> {
     where
         PINS: Pins<SPI2>
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
-
-        // Enable clock for SPI
-        rcc.apb1enr.modify(|_, w| w.spi2en().set_bit());
+        // Enable clock for SPI and reset it
+        SPI2::enable();
+        SPI2::reset();
 
-        Spi { spi, pins }.init(mode, freq, clocks.pclk1())
+        Spi { spi, pins }.init(mode, freq, SPI2::clock(&clocks))
     }
 }
 
@@ -813,13 +809,11 @@ impl<PINS> Spi<SPI3, PINS> {
     where
         PINS: Pins<SPI3>
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
+        // Enable clock for SPI and reset it
+        SPI3::enable();
+        SPI3::reset();
 
-        // Enable clock for SPI
-        rcc.apb1enr.modify(|_, w| w.spi3en().set_bit());
-
-        Spi { spi, pins }.init(mode, freq, clocks.pclk1())
+        Spi { spi, pins }.init(mode, freq, SPI3::clock(&clocks))
     }
 }
 
@@ -842,13 +836,11 @@ impl<PINS> Spi<SPI4, PINS> {
     where
         PINS: Pins<SPI4>
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
-
-        // Enable clock for SPI
-        rcc.apb2enr.modify(|_, w| w.spi4en().set_bit());
+        // Enable clock for SPI and reset it
+        SPI4::enable();
+        SPI4::reset();
 
-        Spi { spi, pins }.init(mode, freq, clocks.pclk2())
+        Spi { spi, pins }.init(mode, freq, SPI4::clock(&clocks))
     }
 }
 
@@ -870,13 +862,11 @@ impl<PINS> Spi<SPI5, PINS> {
     where
         PINS: Pins<SPI5>
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
+        // Enable clock for SPI and reset it
+        SPI5::enable();
+        SPI5::reset();
 
-        // Enable clock for SPI
-        rcc.apb2enr.modify(|_, w| w.spi5en().set_bit());
-
-        Spi { spi, pins }.init(mode, freq, clocks.pclk2())
+        Spi { spi, pins }.init(mode, freq, SPI5::clock(&clocks))
     }
 }
 
@@ -893,13 +883,11 @@ impl<PINS> Spi<SPI6, PINS> {
     where
         PINS: Pins<SPI6>
     {
-        // NOTE(unsafe) This executes only during initialisation
-        let rcc = unsafe { &(*RCC::ptr()) };
-
-        // Enable clock for SPI
-        rcc.apb2enr.modify(|_, w| w.spi6en().set_bit());
+        // Enable clock for SPI and reset it
+        SPI6::enable();
+        SPI6::reset();
 
-        Spi { spi, pins }.init(mode, freq, clocks.pclk2())
+        Spi { spi, pins }.init(mode, freq, SPI6::clock(&clocks))
     }
 }
 
@@ -909,6 +897,21 @@ where
 {
     pub fn init(self, mode: Mode, freq: Hertz, clock: Hertz) -> Self
     {
+        self.configure(mode, freq, clock);
+        self
+    }
+
+    /// Reconfigures mode and frequency after the clocks were reconfigured with `CFGR::freeze`
+    pub fn reconfigure(&mut self, mode: Mode, freq: Hertz, clocks: Clocks)
+    where
+        SPI: BusClock,
+    {
+        // the baud rate may only be changed while the SPI bus is disabled
+        self.spi.cr1.modify(|_, w| w.spe().clear_bit());
+        self.configure(mode, freq, SPI::clock(&clocks));
+    }
+
+    fn configure(&self, mode: Mode, freq: Hertz, clock: Hertz) {
         // disable SS output
         self.spi.cr2.write(|w| w.ssoe().clear_bit());
 
@@ -955,8 +958,6 @@ where
             .spe()
             .set_bit()
         });
-
-        self
     }
 
     /// Enable interrupts for the given `event`:
@@ -1,3 +1,7 @@
+use core::convert::TryFrom;
+use core::num::TryFromIntError;
+use core::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+
 /// Bits per second
 #[derive(PartialEq, PartialOrd, Clone, Copy)]
 pub struct Bps(pub u32);
@@ -27,6 +31,9 @@ pub trait U32Ext {
 
     /// Wrap in `MilliSeconds`
     fn ms(self) -> MilliSeconds;
+
+    /// Wrap in `MicroSeconds`
+    fn us(self) -> MicroSeconds;
 }
 
 impl U32Ext for u32 {
@@ -49,6 +56,10 @@ impl U32Ext for u32 {
     fn ms(self) -> MilliSeconds {
         MilliSeconds(self)
     }
+
+    fn us(self) -> MicroSeconds {
+        MicroSeconds(self)
+    }
 }
 
 impl Into<Hertz> for KiloHertz {
@@ -72,3 +83,138 @@ impl Into<KiloHertz> for MegaHertz {
 /// Time unit
 #[derive(PartialEq, PartialOrd, Clone, Copy)]
 pub struct MilliSeconds(pub u32);
+
+/// Time unit
+#[derive(PartialEq, PartialOrd, Clone, Copy)]
+pub struct MicroSeconds(pub u32);
+
+/// A point in time, in microseconds since a monotonic clock was started
+///
+/// The 64 bit count does not overflow for more than 500000 years.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct Instant(pub u64);
+
+/// A span of time, in microseconds
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct Duration(pub u64);
+
+impl Instant {
+    /// Returns the time elapsed from `earlier` to `self`, or zero if `earlier` is later
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        Duration(self.0.saturating_sub(earlier.0))
+    }
+}
+
+impl Duration {
+    /// Returns the duration in whole milliseconds
+    pub fn as_millis(&self) -> u64 {
+        self.0 / 1_000
+    }
+
+    /// Returns the duration in microseconds
+    pub fn as_micros(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Instant {
+        Instant(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, rhs: Duration) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, rhs: Duration) -> Instant {
+        Instant(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, rhs: Duration) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    /// Same as `duration_since`, zero if `rhs` is later
+    fn sub(self, rhs: Instant) -> Duration {
+        self.duration_since(rhs)
+    }
+}
+
+impl TryFrom<Duration> for u32 {
+    type Error = TryFromIntError;
+
+    /// Converts the duration to microseconds, fails if it doesn't fit into 32 bits
+    fn try_from(duration: Duration) -> Result<u32, TryFromIntError> {
+        u32::try_from(duration.0)
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, rhs: Duration) -> Duration {
+        Duration(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Duration {
+    fn add_assign(&mut self, rhs: Duration) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, rhs: Duration) -> Duration {
+        Duration(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Duration {
+    fn sub_assign(&mut self, rhs: Duration) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Mul<u32> for Duration {
+    type Output = Duration;
+
+    fn mul(self, rhs: u32) -> Duration {
+        Duration(self.0 * u64::from(rhs))
+    }
+}
+
+impl Div<u32> for Duration {
+    type Output = Duration;
+
+    fn div(self, rhs: u32) -> Duration {
+        Duration(self.0 / u64::from(rhs))
+    }
+}
+
+impl Into<Duration> for MilliSeconds {
+    fn into(self) -> Duration {
+        Duration(u64::from(self.0) * 1_000)
+    }
+}
+
+impl Into<Duration> for MicroSeconds {
+    fn into(self) -> Duration {
+        Duration(u64::from(self.0))
+    }
+}
@@ -1,13 +1,15 @@
 //! Timers
 
+use core::sync::atomic::{AtomicU32, Ordering};
+
 use cast::{u16, u32};
+use cortex_m::interrupt;
 use cortex_m::peripheral::syst::SystClkSource;
 use cortex_m::peripheral::SYST;
 use embedded_hal::timer::{CountDown, Periodic};
 use nb;
 use void::Void;
 
-use crate::stm32::RCC;
 #[cfg(any(
     feature = "stm32f405",
     feature = "stm32f407",
@@ -83,8 +85,8 @@ use crate::stm32::{TIM10, TIM2, TIM3, TIM4};
 ))]
 use crate::stm32::{TIM12, TIM13, TIM14, TIM7, TIM8};
 
-use crate::rcc::Clocks;
-use crate::time::Hertz;
+use crate::rcc::{BusTimerClock, Clocks, Enable, Reset};
+use crate::time::{Duration, Hertz, Instant};
 
 /// Hardware timers
 pub struct Timer<TIM> {
@@ -92,6 +94,29 @@ pub struct Timer<TIM> {
     tim: TIM,
 }
 
+/// Free running monotonic clock with a resolution of about 1 µs
+///
+/// The counter runs at the timer clock divided down to the closest frequency of at least 1 MHz,
+/// `now` converts its ticks into microseconds. The 32 bit counter of TIM2 or TIM5 overflows
+/// every 71.6 minutes at 1 MHz, the overflows are counted to extend it to 64 bits. Either `now`
+/// has to be called at least once per overflow period or the update interrupt has to be
+/// enabled with `listen` and `on_interrupt` called from its handler.
+///
+/// ```
+/// let _mono = MonoTimer::tim2(dp.TIM2, clocks);
+///
+/// let start = MonoTimer::<TIM2>::now();
+/// // ...
+/// let elapsed = MonoTimer::<TIM2>::elapsed(start);
+/// ```
+///
+/// With the `rtic` feature the clock implements `rtic::Monotonic`, so it can be used as the
+/// `monotonic` of an RTIC app to schedule tasks in microseconds. RTIC resets it to zero at
+/// the end of `init`.
+pub struct MonoTimer<TIM> {
+    tim: TIM,
+}
+
 /// Interrupt events
 pub enum Event {
     /// Timer timed out / count down ended
@@ -153,7 +178,7 @@ impl CountDown for Timer<SYST> {
 impl Periodic for Timer<SYST> {}
 
 macro_rules! hal {
-    ($($TIM:ident: ($tim:ident, $timXen:ident, $timXrst:ident, $apbenr:ident, $apbrstr:ident, $pclk:ident, $ppre:ident),)+) => {
+    ($($TIM:ident: $tim:ident,)+) => {
         $(
             impl Timer<$TIM> {
                 /// Configures a TIM peripheral as a periodic count down timer
@@ -162,10 +187,8 @@ macro_rules! hal {
                     T: Into<Hertz>,
                 {
                     // enable and reset peripheral to a clean slate state
-                    let rcc = unsafe { &(*RCC::ptr()) };
-                    rcc.$apbenr.modify(|_, w| w.$timXen().set_bit());
-                    rcc.$apbrstr.modify(|_, w| w.$timXrst().set_bit());
-                    rcc.$apbrstr.modify(|_, w| w.$timXrst().clear_bit());
+                    $TIM::enable();
+                    $TIM::reset();
 
                     let mut timer = Timer {
                         clocks,
@@ -212,6 +235,12 @@ macro_rules! hal {
                     }
                 }
 
+                /// Updates the timer clock after the clocks were reconfigured with `CFGR::freeze`,
+                /// takes effect with the next `start`
+                pub fn reconfigure(&mut self, clocks: Clocks) {
+                    self.clocks = clocks;
+                }
+
                 /// Releases the TIM peripheral
                 pub fn release(self) -> $TIM {
                     // pause counter
@@ -233,8 +262,7 @@ macro_rules! hal {
                     self.tim.cnt.reset();
 
                     let frequency = timeout.into().0;
-                    let pclk_mul = if self.clocks.$ppre() == 1 { 1 } else { 2 };
-                    let ticks = self.clocks.$pclk().0 * pclk_mul / frequency;
+                    let ticks = $TIM::timer_clock(&self.clocks).0 / frequency;
 
                     let psc = u16((ticks - 1) / (1 << 16)).unwrap();
                     self.tim.psc.write(|w| w.psc().bits(psc) );
@@ -281,10 +309,10 @@ macro_rules! hal {
     feature = "stm32f479"
 ))]
 hal! {
-    TIM1: (tim1, tim1en, tim1rst, apb2enr, apb2rstr, pclk2, ppre2),
-    TIM5: (tim5, tim5en, tim5rst, apb1enr, apb1rstr, pclk1, ppre1),
-    TIM9: (tim9, tim9en, tim9rst, apb2enr, apb2rstr, pclk1, ppre1),
-    TIM11: (tim11, tim11en, tim11rst, apb2enr, apb2rstr, pclk2, ppre2),
+    TIM1: tim1,
+    TIM5: tim5,
+    TIM9: tim9,
+    TIM11: tim11,
 }
 
 #[cfg(any(
@@ -306,10 +334,10 @@ hal! {
     feature = "stm32f479"
 ))]
 hal! {
-    TIM2: (tim2, tim2en, tim2rst, apb1enr, apb1rstr, pclk1, ppre1),
-    TIM3: (tim3, tim3en, tim3rst, apb1enr, apb1rstr, pclk1, ppre1),
-    TIM4: (tim4, tim4en, tim4rst, apb1enr, apb1rstr, pclk1, ppre1),
-    TIM10: (tim10, tim10en, tim10rst, apb2enr, apb2rstr, pclk2, ppre2),
+    TIM2: tim2,
+    TIM3: tim3,
+    TIM4: tim4,
+    TIM10: tim10,
 }
 
 #[cfg(any(
@@ -330,7 +358,7 @@ hal! {
     feature = "stm32f479"
 ))]
 hal! {
-    TIM6: (tim6, tim6en, tim6rst, apb1enr, apb1rstr, pclk1, ppre1),
+    TIM6: tim6,
 }
 
 #[cfg(any(
@@ -350,9 +378,225 @@ hal! {
     feature = "stm32f479"
 ))]
 hal! {
-    TIM7: (tim7, tim7en, tim7rst, apb1enr, apb1rstr, pclk1, ppre1),
-    TIM8: (tim8, tim8en, tim8rst, apb2enr, apb2rstr, pclk2, ppre2),
-    TIM12: (tim12, tim12en, tim12rst, apb1enr, apb1rstr, pclk1, ppre1),
-    TIM13: (tim13, tim13en, tim13rst, apb1enr, apb1rstr, pclk1, ppre1),
-    TIM14: (tim14, tim14en, tim14rst, apb1enr, apb1rstr, pclk1, ppre1),
+    TIM7: tim7,
+    TIM8: tim8,
+    TIM12: tim12,
+    TIM13: tim13,
+    TIM14: tim14,
+}
+
+// The highest division of `clk` giving at least 1 MHz, the prescaler divides by 1 to 65536
+fn mono_prescaler(clk: u32) -> u32 {
+    ((clk / 1_000_000).max(1) - 1).min(0xffff)
+}
+
+// SysTick (core clock) frequency in Hz, RTIC converts durations into its ticks
+#[cfg(feature = "rtic")]
+static MONO_HCLK: AtomicU32 = AtomicU32::new(16_000_000);
+
+// Reduces `a / b` to lowest terms, so RTIC's conversion overflows as late as possible
+#[cfg(feature = "rtic")]
+fn reduce(a: u32, b: u32) -> rtic::Fraction {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        let r = x % y;
+        x = y;
+        y = r;
+    }
+    let gcd = x.max(1);
+    rtic::Fraction {
+        numerator: a / gcd,
+        denominator: b / gcd,
+    }
+}
+
+macro_rules! mono {
+    ($($TIM:ident: ($tim:ident, $OVERFLOWS:ident, $FREQ:ident),)+) => {
+        $(
+            // Number of counter overflows, only modified in critical sections
+            static $OVERFLOWS: AtomicU32 = AtomicU32::new(0);
+            // Counter frequency in Hz
+            static $FREQ: AtomicU32 = AtomicU32::new(1_000_000);
+
+            impl MonoTimer<$TIM> {
+                /// Starts a TIM peripheral as monotonic clock counting from zero
+                pub fn $tim(tim: $TIM, clocks: Clocks) -> Self {
+                    // enable and reset peripheral to a clean slate state
+                    $TIM::enable();
+                    $TIM::reset();
+
+                    let clk = $TIM::timer_clock(&clocks).0;
+                    let psc = mono_prescaler(clk);
+                    $FREQ.store(clk / (psc + 1), Ordering::Relaxed);
+                    #[cfg(feature = "rtic")]
+                    MONO_HCLK.store(clocks.hclk().0, Ordering::Relaxed);
+
+                    tim.psc.write(|w| w.psc().bits(psc as u16));
+                    tim.arr.write(|w| unsafe { w.bits(u32::max_value()) });
+
+                    // load the prescaler and discard the update event this generates
+                    tim.egr.write(|w| w.ug().set_bit());
+                    tim.sr.modify(|_, w| w.uif().clear_bit());
+                    $OVERFLOWS.store(0, Ordering::Relaxed);
+
+                    tim.cr1.modify(|_, w| w.cen().set_bit());
+
+                    MonoTimer { tim }
+                }
+
+                /// Returns the current time
+                pub fn now() -> Instant {
+                    interrupt::free(|_| {
+                        // NOTE(unsafe) the update flag is only cleared in critical sections and
+                        // the counter is only read
+                        let tim = unsafe { &(*$TIM::ptr()) };
+
+                        let mut cnt = tim.cnt.read().bits();
+                        if tim.sr.read().uif().bit_is_set() {
+                            // count the pending overflow, the counter may have wrapped after
+                            // it was read
+                            tim.sr.modify(|_, w| w.uif().clear_bit());
+                            $OVERFLOWS.fetch_add(1, Ordering::Relaxed);
+                            cnt = tim.cnt.read().bits();
+                        }
+
+                        let overflows = u64::from($OVERFLOWS.load(Ordering::Relaxed));
+                        let ticks = overflows << 32 | u64::from(cnt);
+                        let freq = u64::from($FREQ.load(Ordering::Relaxed));
+                        // split the conversion to microseconds so it can't overflow
+                        Instant(ticks / freq * 1_000_000 + ticks % freq * 1_000_000 / freq)
+                    })
+                }
+
+                /// Returns the frequency the counter runs at
+                pub fn counter_frequency(&self) -> Hertz {
+                    Hertz($FREQ.load(Ordering::Relaxed))
+                }
+
+                /// Adapts the counter to the timer clock after the clocks were reconfigured with
+                /// `CFGR::freeze`, `now` continues from the time it had
+                ///
+                /// Call this right after `freeze`, the time that passes in between is counted
+                /// with the old frequency.
+                pub fn reconfigure(&mut self, clocks: Clocks) {
+                    let clk = $TIM::timer_clock(&clocks).0;
+                    let psc = mono_prescaler(clk);
+                    let freq = u64::from(clk / (psc + 1));
+
+                    interrupt::free(|_| {
+                        let now = Self::now().0;
+
+                        // load the prescaler and discard the update event this generates
+                        self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                        self.tim.psc.write(|w| w.psc().bits(psc as u16));
+                        self.tim.egr.write(|w| w.ug().set_bit());
+                        self.tim.sr.modify(|_, w| w.uif().clear_bit());
+
+                        // continue from the current time in ticks of the new frequency
+                        let ticks = now / 1_000_000 * freq + now % 1_000_000 * freq / 1_000_000;
+                        self.tim.cnt.write(|w| unsafe { w.bits(ticks as u32) });
+                        $OVERFLOWS.store((ticks >> 32) as u32, Ordering::Relaxed);
+                        $FREQ.store(freq as u32, Ordering::Relaxed);
+                        #[cfg(feature = "rtic")]
+                        MONO_HCLK.store(clocks.hclk().0, Ordering::Relaxed);
+
+                        self.tim.cr1.modify(|_, w| w.cen().set_bit());
+                    });
+                }
+
+                /// Returns the time elapsed since `since`
+                pub fn elapsed(since: Instant) -> Duration {
+                    Self::now().duration_since(since)
+                }
+
+                /// Counts a pending overflow, to be called from the timer interrupt handler
+                pub fn on_interrupt() {
+                    Self::now();
+                }
+
+                /// Enables the update interrupt, so overflows are counted without calls to `now`
+                ///
+                /// Note, you will also have to enable the interrupt in the NVIC and call
+                /// `on_interrupt` from its handler.
+                pub fn listen(&mut self) {
+                    self.tim.dier.modify(|_, w| w.uie().set_bit());
+                }
+
+                /// Disables the update interrupt
+                pub fn unlisten(&mut self) {
+                    self.tim.dier.modify(|_, w| w.uie().clear_bit());
+                }
+
+                /// Restarts the clock from zero
+                pub fn reset(&mut self) {
+                    interrupt::free(|_| {
+                        self.tim.cnt.reset();
+                        self.tim.sr.modify(|_, w| w.uif().clear_bit());
+                        $OVERFLOWS.store(0, Ordering::Relaxed);
+                    });
+                }
+
+                /// Stops the clock and releases the TIM peripheral
+                pub fn release(self) -> $TIM {
+                    self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+                    self.tim
+                }
+            }
+
+            #[cfg(feature = "rtic")]
+            impl rtic::Monotonic for MonoTimer<$TIM> {
+                type Instant = Instant;
+
+                fn ratio() -> rtic::Fraction {
+                    // `now` counts microseconds
+                    reduce(MONO_HCLK.load(Ordering::Relaxed), 1_000_000)
+                }
+
+                fn now() -> Instant {
+                    Self::now()
+                }
+
+                unsafe fn reset() {
+                    interrupt::free(|_| {
+                        // NOTE(unsafe) RTIC calls this once at the end of `init`, before any
+                        // task can read the clock
+                        let tim = &(*$TIM::ptr());
+                        tim.cnt.reset();
+                        tim.sr.modify(|_, w| w.uif().clear_bit());
+                        $OVERFLOWS.store(0, Ordering::Relaxed);
+                    });
+                }
+
+                fn zero() -> Instant {
+                    Instant(0)
+                }
+            }
+        )+
+    }
+}
+
+mono! {
+    TIM5: (tim5, TIM5_OVERFLOWS, TIM5_FREQ),
+}
+
+#[cfg(any(
+    feature = "stm32f401",
+    feature = "stm32f405",
+    feature = "stm32f407",
+    feature = "stm32f411",
+    feature = "stm32f412",
+    feature = "stm32f413",
+    feature = "stm32f415",
+    feature = "stm32f417",
+    feature = "stm32f423",
+    feature = "stm32f427",
+    feature = "stm32f429",
+    feature = "stm32f437",
+    feature = "stm32f439",
+    feature = "stm32f446",
+    feature = "stm32f469",
+    feature = "stm32f479"
+))]
+mono! {
+    TIM2: (tim2, TIM2_OVERFLOWS, TIM2_FREQ),
 }
@@ -3,6 +3,7 @@
 use crate::{
     stm32::{IWDG, DBGMCU},
     hal::watchdog::{Watchdog, WatchdogEnable},
+    rcc::ResetReason,
     time::MilliSeconds,
 };
 
@@ -24,6 +25,13 @@ impl IndependentWatchdog {
         IndependentWatchdog { iwdg }
     }
 
+    /// Returns whether the last reset was caused by the independent watchdog
+    ///
+    /// Call this before `ResetReason::clear`, which clears the flag.
+    pub fn caused_last_reset() -> bool {
+        ResetReason::read() == ResetReason::IndependentWatchdog
+    }
+
     /// Debug independent watchdog stopped when core is halted
     pub fn stop_on_debug(&self, dbgmcu: &DBGMCU, stop: bool) {
         dbgmcu.apb1_fz.modify(|_, w| w.dbg_iwdg_stop().bit(stop));
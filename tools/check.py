@@ -34,6 +34,14 @@ def main():
 
     if 'size_check' in sys.argv:
         cargo_cmd = ['cargo', 'build', '--release']
+    elif 'test' in sys.argv:
+        # The unit tests run on the host, without the runtime
+        host = [line.split()[1]
+                for line in subprocess.check_output(["rustc", "-vV"],
+                                                    universal_newlines=True).splitlines()
+                if line.startswith("host:")][0]
+        cargo_cmd = ['cargo', 'test', '--lib', '--target={}'.format(host)]
+        features = [f[:-len(",rt")] for f in features]
     else:
         cargo_cmd = ['cargo', 'check']
 